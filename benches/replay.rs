@@ -0,0 +1,91 @@
+//! Measures how much work is avoided by skipping packets which no script is interested in.
+//!
+//! Run with `cargo bench --bench replay`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use biscuit::config::Config;
+use biscuit::{Biscuit, Direction};
+
+/// The number of packets sent for each measurement.
+const PACKETS: u32 = 20_000;
+
+/// A script which identifies `Ping` and `Pong` from the first packet it sees.
+const SCRIPT: &str = r#"
+var PACKET_NAME = "Ping";
+
+function compare(id, header, data) {
+    if (data.varint(1) !== 7) {
+        return false;
+    }
+
+    identify("Ping", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+    identify("Pong", id + 1, { field_name: "value", field_type: "uint32", field_id: 1 });
+    return true;
+}
+"#;
+
+/// Appends a varint to the buffer.
+fn varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    data.push(value as u8);
+}
+
+/// Encodes a packet with a marker field and many string fields.
+fn packet() -> Vec<u8> {
+    let mut data = vec![0x08, 0x07];
+    for field_id in 2..66u64 {
+        let value = format!("value of field {}", field_id).repeat(4);
+        varint(&mut data, field_id << 3 | 2);
+        varint(&mut data, value.len() as u64);
+        data.extend_from_slice(value.as_bytes());
+    }
+
+    data
+}
+
+/// Measures the time taken to run a function for every packet.
+fn measure(mut run: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..PACKETS {
+        run();
+    }
+
+    start.elapsed() / PACKETS
+}
+
+fn main() {
+    let instance = Biscuit::new();
+    let sources = HashMap::from([("ping.js".to_string(), SCRIPT.to_string())]);
+    instance.initialize_from_sources(Config::default(), sources)
+        .expect("failed to load the benchmark script");
+
+    // Identify `Ping` as packet 1 and `Pong` as packet 2.
+    let data = packet();
+    instance.input(1, Direction::Unknown, &[], &data)
+        .expect("failed to identify the benchmark packets");
+
+    // Packet 2 is known, and the only script identifies packet 1, so it is not decoded.
+    let skipped = measure(|| {
+        instance.input(2, Direction::Unknown, &[], &data).unwrap();
+    });
+
+    // This is the work which skipping a packet avoids.
+    let decoded = measure(|| {
+        protoshark::decode(&data).unwrap();
+    });
+
+    // Packet 1 is decoded and compared by the script.
+    let compared = measure(|| {
+        instance.input(1, Direction::Unknown, &[], &data).unwrap();
+    });
+
+    println!("packet size:       {} bytes", data.len());
+    println!("skipped packet:    {:?}", skipped);
+    println!("decode only:       {:?}", decoded);
+    println!("compared packet:   {:?}", compared);
+}
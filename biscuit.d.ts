@@ -4,6 +4,52 @@ declare global {
      */
     export const env: Map<string, string>;
 
+    /**
+     * A read-only view of the matcher's configuration.
+     */
+    export const config: Readonly<Record<string, any>>;
+
+    /**
+     * A key-value store for this script.
+     *
+     * Values persist between calls to `compare`.
+     */
+    export const store: Map<string, any>;
+
+    /**
+     * A key-value store shared between all scripts.
+     *
+     * Values are copied as JSON, so only JSON-compatible values can be stored.
+     *
+     * Scripts may run on different threads, so each `get` and `set` is atomic,
+     * but a sequence of calls is not. Another script may update a value
+     * between a `get` and a following `set`.
+     */
+    export const shared: {
+        /**
+         * Fetches a value from the shared store.
+         *
+         * @param key The key of the value.
+         */
+        get(key: string): any | undefined;
+
+        /**
+         * Stores a value in the shared store.
+         * Setting a value to `undefined` removes it.
+         *
+         * @param key The key of the value.
+         * @param value The value to store.
+         */
+        set(key: string, value: any): void;
+    };
+
+    /**
+     * The packet names of every loaded comparer.
+     *
+     * This is only available to the script set in `Config::post_load_script`.
+     */
+    export const LOADED_NAMES: readonly string[] | undefined;
+
     /**
      * The global module object.
      */
@@ -12,23 +58,55 @@ declare global {
     /**
      * Imports a module.
      *
-     * @param module The path to the module, relative to the current script.
+     * If the path ends in `.json`, the file is parsed and returned as an object.
+     *
+     * Each file is only evaluated once. Later calls return the same exports.
+     * If files require each other, the file which is still being evaluated returns its partial `module.exports`.
+     *
+     * Files are evaluated inside of a function, like CommonJS modules, so their declarations are not global.
+     *
+     * Files outside of the script's directory are rejected, unless `Config::allow_require_outside_root` is set.
+     *
+     * @param module The path to the module, relative to the file calling `require`.
      */
     export function require(module: string): undefined | any;
 
+    /**
+     * Imports a module once for all scripts.
+     *
+     * The first script to call this evaluates the file, as if by {@link require}.
+     * Every script, including the first, is given a copy of its exports.
+     * Exports which are not JSON-compatible, such as functions, are rejected with an error.
+     *
+     * If the file fails to evaluate, every script is given the same error without evaluating it again.
+     *
+     * @param module The path to the module, relative to the file calling `requireOnce`.
+     */
+    export function requireOnce(module: string): undefined | any;
+
     /**
      * Logs an info message to the console.
+     * Like `console.log`, every argument is logged, separated by spaces.
      *
-     * @param message The message to log.
+     * @param message The values to log.
      */
-    export function info(message: string): void;
+    export function info(...message: any[]): void;
 
     /**
      * Logs a warning message to the console.
+     * Like `console.log`, every argument is logged, separated by spaces.
      *
-     * @param message The message to log. 
+     * @param message The values to log.
      */
-    export function warn(message: string): void;
+    export function warn(...message: any[]): void;
+
+    /**
+     * Logs an error message to the console.
+     * Like `console.log`, every argument is logged, separated by spaces.
+     *
+     * @param message The values to log.
+     */
+    export function error(...message: any[]): void;
 
     /**
      * Decodes a Base64-encoded string.
@@ -46,19 +124,214 @@ declare global {
      */
     export function rsaDecrypt(privateKey: string, encryptedData: string): ArrayBuffer;
 
+    /**
+     * AES-GCM decrypts a message, verifying its authentication tag.
+     * Each value can be an `ArrayBuffer` or a Base64-encoded string.
+     *
+     * Throws `invalid key length` if the key is not 16 or 32 bytes long,
+     * and `tag verification failed` if the message or key is incorrect.
+     *
+     * @param key The 128-bit or 256-bit key.
+     * @param nonce The 96-bit nonce.
+     * @param ciphertext The encrypted data. The tag should be appended, unless it is provided separately.
+     * @param aad Additional authenticated data, if any.
+     * @param tag The authentication tag, if it is not appended to the ciphertext.
+     */
+    export function aesGcmDecrypt(
+        key: ArrayBuffer | string,
+        nonce: ArrayBuffer | string,
+        ciphertext: ArrayBuffer | string,
+        aad?: ArrayBuffer | string,
+        tag?: ArrayBuffer | string
+    ): ArrayBuffer;
+
+    /**
+     * Verifies a signature against a public key.
+     * Each value can be an `ArrayBuffer` or a Base64-encoded string.
+     *
+     * Returns `false` if the signature does not match,
+     * but throws if the key or signature is malformed.
+     *
+     * @param algorithm The signature algorithm. Only `ed25519` is supported.
+     * @param publicKey The public key.
+     * @param message The signed message.
+     * @param signature The signature.
+     */
+    export function verifySignature(
+        algorithm: "ed25519",
+        publicKey: ArrayBuffer | string,
+        message: ArrayBuffer | string,
+        signature: ArrayBuffer | string
+    ): boolean;
+
+    /**
+     * Fetches an environment variable.
+     * Returns `undefined` if the variable does not exist.
+     *
+     * @param name The name of the variable.
+     */
+    export function getEnv(name: string): string | undefined;
+
+    /**
+     * Fetches an environment variable as a number.
+     * Returns the fallback if the variable does not exist or is not a number.
+     *
+     * @param name The name of the variable.
+     * @param fallback The value to return instead.
+     */
+    export function getEnvNumber<T = undefined>(name: string, fallback?: T): number | T;
+
+    /**
+     * Fetches an environment variable as a boolean.
+     * `true`, `1`, and `yes` are true, while `false`, `0`, and `no` are false, ignoring case.
+     * Returns the fallback if the variable does not exist or is not one of these values.
+     *
+     * @param name The name of the variable.
+     * @param fallback The value to return instead.
+     */
+    export function getEnvBool<T = undefined>(name: string, fallback?: T): boolean | T;
+
+    /**
+     * Fetches the current time as milliseconds since the Unix epoch.
+     */
+    export function now(): number;
+
+    /**
+     * Generates a random number between `0` (inclusive) and `1` (exclusive).
+     *
+     * Unlike `Math.random`, this is reproducible when a seed is configured.
+     */
+    export function random(): number;
+
+    /**
+     * Reads a protobuf-style varint from a buffer.
+     * Returns the value and the number of bytes read.
+     *
+     * @param buffer The buffer to read from.
+     * @param offset The byte offset to start reading at. Defaults to `0`.
+     */
+    export function readVarInt(buffer: ArrayBuffer, offset?: number): [number, number];
+
+    /**
+     * Calculates the CRC-32 checksum of a buffer.
+     *
+     * @param buffer The buffer to checksum.
+     * @param preset The algorithm to use. Defaults to `ISO_HDLC`.
+     */
+    export function crc32(
+        buffer: ArrayBuffer,
+        preset?: "ISO_HDLC" | "BZIP2" | "CKSUM" | "ISCSI" | "JAMCRC" | "MPEG_2" | "XFER"
+    ): number;
+
+    /**
+     * Calculates the CRC-16 checksum of a buffer.
+     *
+     * @param buffer The buffer to checksum.
+     * @param preset The algorithm to use. Defaults to `ARC`.
+     */
+    export function crc16(
+        buffer: ArrayBuffer,
+        preset?: "ARC" | "IBM_3740" | "KERMIT" | "MODBUS" | "USB" | "XMODEM"
+    ): number;
+
+    /**
+     * Decodes a buffer as a protobuf message.
+     * Throws if the buffer is not a valid message.
+     * The error describes the problem, such as a truncated field or an invalid wire type.
+     *
+     * @param buffer The encoded message.
+     */
+    export function decodePacket(buffer: ArrayBuffer): SerializedMessage;
+
+    /**
+     * Decodes a buffer as UTF-8 text.
+     * Invalid sequences are replaced with `U+FFFD`.
+     *
+     * @param buffer The encoded text.
+     */
+    export function decodeUtf8(buffer: ArrayBuffer): string;
+
+    /**
+     * Decodes a buffer as Latin-1 (ISO-8859-1) text.
+     * Every byte maps to the character with the same code point.
+     *
+     * @param buffer The encoded text.
+     */
+    export function decodeLatin1(buffer: ArrayBuffer): string;
+
+    /**
+     * Registers the value names of an enum field.
+     * Registering the same field again replaces its names.
+     *
+     * @param messageName The name of the message the field belongs to.
+     * @param fieldId The ID of the field.
+     * @param mapping Either a map of values to names (`{ 0: "NONE" }`), or names to values (`{ NONE: 0 }`).
+     */
+    export function registerEnum(
+        messageName: string,
+        fieldId: number,
+        mapping: Record<number, string> | Record<string, number>
+    ): void;
+
+    /**
+     * Suggests a protobuf type for a field of a {@link SerializedMessage}, based on how it was encoded.
+     * Varints are suggested as `int64`, nested messages as `bytes`, and fields which occur more than once as `repeated`.
+     * Returns `undefined` if the field does not exist.
+     *
+     * @param message The message containing the field.
+     * @param fieldId The ID of the field.
+     */
+    export function inferType(message: SerializedMessage, fieldId: number): string | undefined;
+
     /**
      * Identifies a packet.
      *
      * @param packetName The name of the packet.
      * @param packetId The ID of the packet.
      * @param fieldData The data of an individual field.
+     * @param confidence How confident the identification is, from `0.0` to `1.0`.
+     *                   An existing identification is only replaced by one with a higher confidence.
+     *                   If omitted, the identification is treated as certain.
      */
     export function identify(
         packetName: string,
         packetId: number,
-        fieldData: FieldData
+        fieldData: FieldData,
+        confidence?: number
     ): void;
 
+    /**
+     * Identifies multiple fields of a packet at once.
+     * Each field is applied in order, as if by {@link identify}.
+     *
+     * @param packetName The name of the packet.
+     * @param packetId The ID of the packet.
+     * @param fields The data of each field. A field's `confidence` is used if it is set.
+     */
+    export function identifyAll(
+        packetName: string,
+        packetId: number,
+        fields: FieldData[]
+    ): void;
+
+    /**
+     * Checks if a packet ID is being seen for the first time.
+     * This returns `true` exactly once per ID, and is shared between all scripts.
+     *
+     * @param packetId The ID of the packet.
+     */
+    export function firstSeen(packetId: number): boolean;
+
+    /**
+     * Records a packet's name without identifying any fields.
+     *
+     * If the packet ID is already known, this does nothing.
+     *
+     * @param packetName The name of the packet.
+     * @param packetId The ID of the packet.
+     */
+    export function guessName(packetName: string, packetId: number): void;
+
     /**
      * Checks if a packet is known.
      *
@@ -66,6 +339,15 @@ declare global {
      */
     export function isKnown(packetId: string | number): boolean;
 
+    /**
+     * Checks if a field of a packet has been identified.
+     *
+     * @param packetName The name of the packet.
+     * @param fieldId The ID of the field.
+     * @param fieldType The type the field must have been identified with. If omitted, any type matches.
+     */
+    export function fieldKnown(packetName: string, fieldId: number, fieldType?: string): boolean;
+
     /** Represents `matcher.rs#MessageField` */
     export interface FieldData {
         /**
@@ -77,6 +359,9 @@ declare global {
 
         /**
          * The type of the field.
+         *
+         * This should be a scalar type (`int32`, `string`, ...), a message or enum name
+         * starting with an uppercase letter, or a map (`map<string, int32>`).
          */
         field_type: string;
 
@@ -85,6 +370,11 @@ declare global {
          * This must be unique.
          */
         field_id: number;
+
+        /**
+         * How confident the identification is, from `0.0` to `1.0`.
+         */
+        confidence?: number;
     }
     
     /** Represents `matcher.rs#Packet` */
@@ -96,8 +386,26 @@ declare global {
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        get<T = undefined>(key: number, fallback?: T): MessageValue | T;
+
+        /**
+         * Fetches a value by the name of an identified field.
+         * Returns `None` if the field has not been identified or does not exist.
+         *
+         * @param messageName The name of the message the field belongs to.
+         * @param fieldName The name of the field.
          */
-        get(key: number): MessageValue | undefined;
+        getByName(messageName: string, fieldName: string): MessageValue | undefined;
+
+        /**
+         * Converts the message into an object, keyed by field ID.
+         * Bytes are represented as arrays of numbers.
+         *
+         * This is called by `JSON.stringify`, and is used when a message is logged.
+         */
+        toJSON(): Record<number, any>;
 
         /**
          * Returns all keys in the message.
@@ -106,52 +414,268 @@ declare global {
          */
         keys(): number[];
 
+        /**
+         * Returns all fields in the message as `[fieldId, value]` pairs.
+         */
+        entries(): [number, MessageValue][];
+
+        /**
+         * Finds the first field matching a predicate, in order of field ID.
+         * Returns the matching `[fieldId, value]` pair, or `None` if no field matches.
+         *
+         * @param predicate Called with the ID and value of each field.
+         */
+        find(predicate: (fieldId: number, value: MessageValue) => boolean): [number, MessageValue] | undefined;
+
+        /**
+         * Transforms each field in the message, in order of field ID.
+         * Results which are `undefined` are left out of the returned array.
+         *
+         * @param callback Called with the ID and value of each field.
+         */
+        map<T>(callback: (fieldId: number, value: MessageValue) => T | undefined): T[];
+
+        /**
+         * Fetches the encoded bytes of a field.
+         * For length-delimited fields, this excludes the length prefix.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         */
+        raw(key: number): ArrayBuffer | undefined;
+
+        /**
+         * Counts the number of times a field occurs in the message.
+         * Returns `1` for a single value, the number of occurrences for a repeated field, or `0` if the field does not exist.
+         * Packed repeated fields are counted as a single occurrence.
+         *
+         * @param key The field ID.
+         */
+        count(key: number): number;
+
+        /**
+         * Fetches every value of a field, in the order they occur.
+         * Other methods return the last value of a repeated field.
+         * Returns an empty array if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         */
+        values(key: number): MessageValue[];
+
+        /**
+         * Fetches the protobuf wire type of a field.
+         * This is `0` for varints, `1` for 64-bit values, `2` for length-delimited values, and `5` for 32-bit values.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         */
+        wireType(key: number): 0 | 1 | 2 | 5 | undefined;
+
         /**
          * Fetches a `VarInt` field from the message.
          * Returns `None` if the field with the given ID does not exist.
          *
+         * The value is not zigzag-decoded. Use {@link sint} for `sint32` and `sint64` fields.
+         *
+         * The value is converted to the closest-fitting number type, so its signedness may be guessed.
+         * Use {@link uint}, {@link int}, or {@link long} to read a specific type.
+         *
+         * Values above `2^53` lose precision. Use {@link bigint} to read them exactly.
+         *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        varint(key: number): number | undefined;
+        varint<T = undefined>(key: number, fallback?: T): number | T;
 
         /**
          * Returns all `VarInt` fields in the message.
          */
         allVarInt(): [number, number][];
 
+        /**
+         * Fetches a `VarInt` field from the message as an unsigned 32-bit integer (`uint32`).
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        uint<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all `VarInt` fields in the message as unsigned 32-bit integers.
+         */
+        allUint(): [number, number][];
+
+        /**
+         * Fetches a `VarInt` field from the message as a signed 32-bit integer (`int32`).
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        int<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all `VarInt` fields in the message as signed 32-bit integers.
+         */
+        allInt(): [number, number][];
+
+        /**
+         * Fetches a `VarInt` field from the message as a signed 64-bit integer (`int64`).
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        long<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all `VarInt` fields in the message as signed 64-bit integers.
+         */
+        allLong(): [number, number][];
+
+        /**
+         * Fetches a `VarInt` field from the message as an unsigned `BigInt`, without any loss of precision.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        bigint<T = undefined>(key: number, fallback?: T): bigint | T;
+
+        /**
+         * Returns all `VarInt` fields in the message as unsigned `BigInt`s.
+         */
+        allBigint(): [number, bigint][];
+
+        /**
+         * Fetches a `VarInt` field from the message, zigzag-decoded as a signed integer.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        sint<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all `VarInt` fields in the message, zigzag-decoded as signed integers.
+         */
+        allSint(): [number, number][];
+
+        /**
+         * Fetches a `VarInt` field from the message as a boolean.
+         * Returns `None` if the field does not exist or is not `0` or `1`.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        bool<T = undefined>(key: number, fallback?: T): boolean | T;
+
+        /**
+         * Returns all `VarInt` fields in the message which are `0` or `1`, as booleans.
+         */
+        allBool(): [number, boolean][];
+
         /**
          * Fetches a `float` field from the message.
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        float(key: number): number | undefined;
+        float<T = undefined>(key: number, fallback?: T): number | T;
 
         /**
          * Returns all `float` fields in the message.
          */
         allFloat(): [number, number][];
 
+        /**
+         * Fetches a 32-bit fixed-width field from the message as an unsigned integer.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * This reads the same fields as {@link float}, but interprets the bits as an integer.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        fixed32<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all 32-bit fixed-width fields in the message as unsigned integers.
+         */
+        allFixed32(): [number, number][];
+
+        /**
+         * Fetches a 32-bit fixed-width field from the message as a signed integer.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * This reads the same fields as {@link float}, but interprets the bits as an integer.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        sfixed32<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all 32-bit fixed-width fields in the message as signed integers.
+         */
+        allSfixed32(): [number, number][];
+
         /**
          * Fetches a `double` field from the message.
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        double(key: number): number | undefined;
+        double<T = undefined>(key: number, fallback?: T): number | T;
 
         /**
          * Returns all `double` fields in the message.
          */
         allDouble(): [number, number][];
 
+        /**
+         * Fetches a 64-bit fixed-width field from the message as an unsigned integer.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * This reads the same fields as {@link double}, but interprets the bits as an integer.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        fixed64<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all 64-bit fixed-width fields in the message as unsigned integers.
+         */
+        allFixed64(): [number, number][];
+
+        /**
+         * Fetches a 64-bit fixed-width field from the message as a signed integer.
+         * Returns `None` if the field with the given ID does not exist.
+         *
+         * This reads the same fields as {@link double}, but interprets the bits as an integer.
+         *
+         * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
+         */
+        sfixed64<T = undefined>(key: number, fallback?: T): number | T;
+
+        /**
+         * Returns all 64-bit fixed-width fields in the message as signed integers.
+         */
+        allSfixed64(): [number, number][];
+
         /**
          * Fetches a `string` field from the message.
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        string(key: number): string | undefined;
+        string<T = undefined>(key: number, fallback?: T): string | T;
 
         /**
          * Returns all `string` fields in the message.
@@ -163,8 +687,9 @@ declare global {
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        bytes(key: number): ArrayBuffer | undefined;
+        bytes<T = undefined>(key: number, fallback?: T): ArrayBuffer | T;
 
         /**
          * Returns all `bytes` fields in the message.
@@ -176,16 +701,98 @@ declare global {
          * Returns `None` if the field with the given ID does not exist.
          *
          * @param key The field ID.
+         * @param fallback The value to return instead of `undefined`.
          */
-        message(key: number): SerializedMessage | undefined;
+        message<T = undefined>(key: number, fallback?: T): SerializedMessage | T;
 
         /**
          * Returns all `SerializedMessage` fields in the message.
          */
         allMessage(): [number, SerializedMessage][];
+
+        /**
+         * Fetches a range of bytes from a `bytes` field.
+         * Like `Array.prototype.slice`, negative indices count from the end, and the range is clamped to the field's length.
+         * Fields decoded as strings or messages are sliced from their encoded bytes.
+         * Returns `None` if the field does not exist or is not length-delimited.
+         *
+         * @param key The field ID.
+         * @param start The index of the first byte. Defaults to `0`.
+         * @param end The index after the last byte. Defaults to the field's length.
+         */
+        slice(key: number, start?: number, end?: number): ArrayBuffer | undefined;
+
+        /**
+         * Checks if another message has the same fields and values, including nested messages.
+         * Returns `false` if the other value is not a message.
+         *
+         * @param other The message to compare against.
+         */
+        equals(other: SerializedMessage): boolean;
+
+        /**
+         * Attempts to decode a `bytes` or `string` field as a nested message.
+         * A field which is valid UTF-8 is read as a string even if it also decodes as a message,
+         * so this is how such fields are read as messages.
+         * If the field is already a message, it is returned with the message methods.
+         * Returns `None` if the field does not exist or cannot be decoded.
+         *
+         * @param key The field ID.
+         */
+        asMessage(key: number): SerializedMessage | undefined;
+
+        /**
+         * Fetches a `VarInt` field as the name registered with {@link registerEnum}.
+         * Returns the number if the value has no name, or `None` if the field does not exist.
+         *
+         * @param key The field ID.
+         * @param messageName The name of the message. Defaults to the name of the packet, if known.
+         */
+        enum(key: number, messageName?: string): string | number | undefined;
     }
     
-    /** Represents a `message.rs#Value` */
+    /**
+     * A handle to the live cache.
+     *
+     * This is passed to a script's `init` function after the environment variables.
+     * Changes made through the handle are kept, and are visible to every script.
+     */
+    export interface CacheHandle {
+        /** Behaves like the global {@link isKnown}. */
+        isKnown(packetId: string | number): boolean;
+
+        /** Behaves like the global {@link fieldKnown}. */
+        fieldKnown(packetName: string, fieldId: number, fieldType?: string): boolean;
+
+        /** Behaves like the global {@link firstSeen}. */
+        firstSeen(packetId: number): boolean;
+
+        /** Behaves like the global {@link guessName}. */
+        guessName(packetName: string, packetId: number): void;
+
+        /** Behaves like the global {@link identify}. */
+        identify(packetName: string, packetId: number, fieldData: FieldData, confidence?: number): void;
+
+        /** Behaves like the global {@link identifyAll}. */
+        identifyAll(packetName: string, packetId: number, fields: FieldData[]): void;
+
+        /** Copies the current contents of the cache. */
+        toJSON(): Record<string, any>;
+    }
+
+    /**
+     * Represents `matcher.rs#Direction`.
+     *
+     * This is passed to a script's `compare` function after the packet data.
+     */
+    export type Direction = "inbound" | "outbound" | "unknown";
+
+    /**
+     * Represents a `message.rs#Value`.
+     *
+     * Nested messages only have the `inner` property, unless `nested_message_methods` is set.
+     * Use {@link SerializedMessage.asMessage} to read one with the message methods.
+     */
     export type MessageValue = number | string | ArrayBuffer | SerializedMessage;
 }
 
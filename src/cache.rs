@@ -0,0 +1,956 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use anyhow::anyhow;
+use boa_engine::{Finalize, Trace};
+use boa_engine::value::TryFromJs;
+use serde::{Deserialize, Serialize};
+use crate::error::BiscuitError;
+
+/// Represents a JavaScript object containing field data.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Trace, Finalize, TryFromJs)]
+pub struct MessageField {
+    /// The name of the field.
+    /// 
+    /// # Repeated Names
+    /// 
+    /// If this field name is repeated, the other fields will be categorized under a `oneof`.
+    pub field_name: String,
+    
+    /// The type of the field.
+    pub field_type: String,
+    
+    /// The ID of the field.
+    /// 
+    /// This must be unique.
+    pub field_id: u16,
+
+    /// How confident the script is in this identification, from `0.0` to `1.0`.
+    ///
+    /// If left unspecified, the identification is treated as certain.
+    #[serde(default)]
+    pub confidence: Option<f64>
+}
+
+impl MessageField {
+    /// The protobuf scalar value types.
+    pub const SCALAR_TYPES: [&'static str; 15] = [
+        "double", "float", "int32", "int64", "uint32", "uint64",
+        "sint32", "sint64", "fixed32", "fixed64", "sfixed32", "sfixed64",
+        "bool", "string", "bytes"
+    ];
+
+    /// Returns the confidence of this identification.
+    pub fn confidence(&self) -> f64 {
+        self.confidence.unwrap_or(1.0)
+    }
+
+    /// Checks if both identifications describe the same field.
+    ///
+    /// Fields are matched by both ID and type, so that variants of a field
+    /// which are identified with different types are kept separately.
+    pub fn same_field(&self, other: &MessageField) -> bool {
+        self.field_id == other.field_id && self.field_type == other.field_type
+    }
+
+    /// Checks if the field's type can be written to a `.proto` file.
+    ///
+    /// Valid types are scalar types, message or enum references, and maps.
+    /// Message and enum references must start with an uppercase letter,
+    /// and may be qualified with a package or parent message (`Outer.Inner`).
+    ///
+    /// The type may be prefixed with `repeated` or `optional`.
+    pub fn validate(&self) -> Result<(), String> {
+        let field_type = self.field_type.trim();
+        let field_type = field_type
+            .strip_prefix("repeated ")
+            .or_else(|| field_type.strip_prefix("optional "))
+            .unwrap_or(field_type)
+            .trim();
+
+        match Self::valid_type(field_type) {
+            true => Ok(()),
+            false => Err(format!(
+                "invalid type '{}' for field '{}'",
+                self.field_type, self.field_name
+            ))
+        }
+    }
+
+    /// Checks if the given type is a scalar type, reference, or map.
+    fn valid_type(field_type: &str) -> bool {
+        if Self::SCALAR_TYPES.contains(&field_type) {
+            return true;
+        }
+
+        // Map keys must be integral or string scalars.
+        if let Some(inner) = field_type
+            .strip_prefix("map<")
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            let Some((key, value)) = inner.split_once(',') else {
+                return false;
+            };
+
+            let key = key.trim();
+            let valid_key = Self::SCALAR_TYPES.contains(&key) &&
+                !matches!(key, "double" | "float" | "bytes");
+
+            return valid_key && Self::valid_type(value.trim());
+        }
+
+        // Otherwise, this should be a reference to a message or enum.
+        field_type.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars.next().is_some_and(|char| char.is_ascii_uppercase()) &&
+                chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+        })
+    }
+}
+
+/// A decoded field value, as stored in the cache.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    /// The raw value of a varint.
+    VarInt(u64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Message(HashMap<i32, FieldValue>)
+}
+
+/// Describes a change made to the cache.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum CacheEvent {
+    /// A packet ID was given a name.
+    NameAdded {
+        name: String,
+        id: u16
+    },
+
+    /// A field of a message was identified.
+    FieldAdded {
+        name: String,
+        field: MessageField
+    },
+
+    /// A field of a message was replaced by a more confident identification.
+    FieldReplaced {
+        name: String,
+        previous: MessageField,
+        field: MessageField
+    },
+
+    /// A packet name was corrected.
+    NameChanged {
+        previous: String,
+        name: String
+    },
+
+    /// The most recent change was reverted.
+    ///
+    /// This contains the events which were reverted, in the order they originally occurred.
+    Undone {
+        events: Vec<CacheEvent>
+    }
+}
+
+/// A field which was identified differently between two caches.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FieldChange {
+    /// The field in the other cache.
+    pub previous: MessageField,
+
+    /// The field in this cache.
+    pub current: MessageField
+}
+
+/// The differences between two caches.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct CacheDiff {
+    /// Packet names which are only in this cache.
+    pub added_names: Vec<String>,
+
+    /// Packet names which are only in the other cache.
+    pub removed_names: Vec<String>,
+
+    /// Fields which are only in this cache, keyed by packet name.
+    pub added_fields: HashMap<String, Vec<MessageField>>,
+
+    /// Fields which were identified differently, keyed by packet name.
+    pub changed_fields: HashMap<String, Vec<FieldChange>>
+}
+
+impl CacheDiff {
+    /// Checks if the caches were identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_names.is_empty() && self.removed_names.is_empty() &&
+            self.added_fields.is_empty() && self.changed_fields.is_empty()
+    }
+}
+
+/// How often the fields of a message were present in received packets.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct FieldPresence {
+    /// The number of packets of this message which were received.
+    pub packets: u64,
+
+    /// The number of those packets each field was present in, keyed by field ID.
+    pub fields: HashMap<i32, u64>
+}
+
+impl FieldPresence {
+    /// Returns the fraction of packets the field was present in, from `0.0` to `1.0`.
+    pub fn ratio(&self, field_id: i32) -> f64 {
+        match self.packets {
+            0 => 0.0,
+            packets => *self.fields.get(&field_id).unwrap_or(&0) as f64 / packets as f64
+        }
+    }
+}
+
+/// Represents the deobfuscated packet cache.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Cache {
+    /// The layout version of the cache, used when loading saved caches.
+    ///
+    /// Caches saved before versioning was added are treated as version `0`.
+    #[serde(default)]
+    version: u32,
+
+    /// This is an array of known packet names, in the order they were identified.
+    ///
+    /// This is not definitive, and is used only for quick reference.
+    /// Lookups should use `name_map` instead.
+    known_names: Vec<String>,
+
+    /// This is an array of known packet IDs, in the order they were identified.
+    ///
+    /// This is not definitive, and is used only for quick reference.
+    /// Lookups should use `id_map` instead.
+    known_ids: Vec<u16>,
+
+    /// This maps packet IDs to their guessed name.
+    id_map: HashMap<u16, String>,
+    
+    /// This maps guessed names to their packet IDs.
+    name_map: HashMap<String, u16>,
+    
+    /// All cached messages.
+    messages: HashMap<String, Vec<MessageField>>,
+
+    /// The fields of the most recent header seen for each packet name.
+    ///
+    /// This is only recorded when `Config::retain_headers` is set.
+    #[serde(default)]
+    headers: HashMap<String, HashMap<i32, FieldValue>>,
+
+    /// How often the fields of each named packet were present.
+    ///
+    /// This is only recorded when `Config::track_field_presence` is set.
+    #[serde(default)]
+    presence: HashMap<String, FieldPresence>,
+
+    /// Packet IDs which have been queried with `first_seen`.
+    ///
+    /// This is not saved with the cache.
+    #[serde(skip)]
+    seen_ids: HashSet<u16>,
+
+    /// Changes which have not yet been sent to listeners.
+    #[serde(skip)]
+    events: Vec<CacheEvent>,
+
+    /// The events of each change made to the cache, most recent last.
+    ///
+    /// This is used to revert changes with `undo_last`, and is not saved with the cache.
+    #[serde(skip)]
+    history: VecDeque<Vec<CacheEvent>>,
+
+    /// The maximum number of changes kept in `history`.
+    ///
+    /// This is not saved with the cache.
+    #[serde(skip)]
+    history_limit: Option<usize>,
+
+    /// A counter which is incremented whenever the cache changes.
+    ///
+    /// This is shared with script realms, which read it without locking the cache,
+    /// and is not saved with the cache.
+    #[serde(skip)]
+    generation: Arc<AtomicU64>
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache {
+            version: Self::VERSION,
+            known_names: vec![],
+            known_ids: vec![],
+            id_map: HashMap::new(),
+            name_map: HashMap::new(),
+            messages: HashMap::new(),
+            headers: HashMap::new(),
+            presence: HashMap::new(),
+            seen_ids: HashSet::new(),
+            events: vec![],
+            history: VecDeque::new(),
+            history_limit: None,
+            generation: Arc::default()
+        }
+    }
+}
+
+impl Cache {
+    /// The current layout version of the cache.
+    pub const VERSION: u32 = 1;
+
+    /// Loads a cache which was saved as JSON.
+    ///
+    /// Version `0` caches load as-is, since every field added since then has a default.
+    /// A migration step is only needed once a layout change cannot be expressed by a default.
+    ///
+    /// Returns an error if the cache was saved by a newer version of the library.
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, BiscuitError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| anyhow!("failed to read cache '{}': {}", path.to_string_lossy(), error))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|error| anyhow!("failed to parse cache '{}': {}", path.to_string_lossy(), error))?;
+
+        // Check the version before reading the rest of the cache.
+        let version = value
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0) as u32;
+        if version > Self::VERSION {
+            return Err(BiscuitError::CacheVersion {
+                found: version,
+                supported: Self::VERSION
+            });
+        }
+
+        let mut cache: Cache = serde_json::from_value(value)
+            .map_err(|error| anyhow!("invalid cache '{}': {}", path.to_string_lossy(), error))?;
+        cache.version = Self::VERSION;
+
+        Ok(cache)
+    }
+
+    /// Fetches the known packet names, in the order they were identified.
+    pub fn known_names(&self) -> &[String] {
+        &self.known_names
+    }
+
+    /// Simple check to see if the cache knows the given ID.
+    pub fn id_known(&self, id: u16) -> bool {
+        self.id_map.contains_key(&id)
+    }
+    
+    /// Simple check to see if the cache knows the given name.
+    pub fn name_known(&self, name: &str) -> bool {
+        self.name_map.contains_key(name)
+    }
+
+    /// Checks if this is the first time the given ID was seen, and records it.
+    ///
+    /// This returns `true` exactly once per ID.
+    pub fn first_seen(&mut self, id: u16) -> bool {
+        self.seen_ids.insert(id)
+    }
+
+    /// Fetches the guessed name of a packet ID.
+    pub fn name_of(&self, id: u16) -> Option<&str> {
+        self.id_map.get(&id).map(|name| name.as_str())
+    }
+
+    /// Fetches the packet ID of a guessed name.
+    pub fn id_of(&self, name: &str) -> Option<u16> {
+        self.name_map.get(name).copied()
+    }
+    
+    /// Checks if the given field of a message has been identified.
+    ///
+    /// If a type is given, the field must have been identified with that type,
+    /// as in `MessageField::same_field`. Otherwise, any type matches.
+    pub fn field_known(&self, name: &str, id: u16, field_type: Option<&str>) -> bool {
+        match self.messages.get(name) {
+            Some(fields) => fields.iter().any(|field| {
+                field.field_id == id && field_type.is_none_or(|field_type| field.field_type == field_type)
+            }),
+            None => false
+        }
+    }
+
+    /// Fetches the ID of an identified field by its name.
+    pub fn field_id(&self, name: &str, field_name: &str) -> Option<u16> {
+        self.messages
+            .get(name)?
+            .iter()
+            .find(|field| field.field_name == field_name)
+            .map(|field| field.field_id)
+    }
+
+    /// Fetches the identified fields of a message.
+    pub fn fields(&self, name: &str) -> Option<&[MessageField]> {
+        self.messages.get(name).map(|fields| fields.as_slice())
+    }
+
+    /// Fetches the fields of the most recent header seen for a packet.
+    pub fn header(&self, name: &str) -> Option<&HashMap<i32, FieldValue>> {
+        self.headers.get(name)
+    }
+
+    /// Records the most recent header seen for a packet.
+    pub(crate) fn update_header(&mut self, name: String, header: HashMap<i32, FieldValue>) {
+        self.headers.insert(name, header);
+    }
+
+    /// Fetches how often the fields of a packet were present.
+    pub fn presence(&self, name: &str) -> Option<&FieldPresence> {
+        self.presence.get(name)
+    }
+
+    /// Records which fields were present in a received packet.
+    pub(crate) fn record_presence(&mut self, name: String, fields: impl IntoIterator<Item = i32>) {
+        let presence = self.presence.entry(name).or_default();
+        presence.packets += 1;
+
+        for field_id in fields {
+            *presence.fields.entry(field_id).or_default() += 1;
+        }
+    }
+
+    /// Updates the cache with the guessed name and ID.
+    ///
+    /// This does not record any fields.
+    /// If the ID is already known, the cache is left unchanged.
+    pub fn update_name(&mut self, message_name: String, packet_id: u16) {
+        let start = self.events.len();
+        self.add_name(message_name, packet_id);
+        self.checkpoint(start);
+    }
+
+    /// Adds the guessed name and ID to the cache, if the ID is not already known.
+    fn add_name(&mut self, message_name: String, packet_id: u16) {
+        if self.id_map.contains_key(&packet_id) {
+            return;
+        }
+
+        self.known_names.push(message_name.clone());
+        self.known_ids.push(packet_id);
+
+        self.id_map.insert(packet_id, message_name.clone());
+        self.name_map.insert(message_name.clone(), packet_id);
+
+        self.push_event(CacheEvent::NameAdded {
+            name: message_name,
+            id: packet_id
+        });
+    }
+
+    /// Updates the cache with the guessed name, ID, and field data.
+    ///
+    /// If the field was already identified, it is only replaced
+    /// when the new identification has a higher confidence.
+    pub fn update(
+        &mut self,
+        message_name: String,
+        packet_id: u16,
+        field: MessageField
+    ) {
+        let start = self.events.len();
+
+        // Add the message to the cache if it doesn't exist.
+        self.add_name(message_name.clone(), packet_id);
+        
+        // Add the field to the message.
+        self.add_field(message_name, field);
+
+        self.checkpoint(start);
+    }
+
+    /// Updates the cache with the guessed name, ID, and multiple fields.
+    ///
+    /// Each field is applied in order, as if by `Cache::update`.
+    pub fn update_many(
+        &mut self,
+        message_name: String,
+        packet_id: u16,
+        fields: impl IntoIterator<Item = MessageField>
+    ) {
+        let start = self.events.len();
+        self.add_name(message_name.clone(), packet_id);
+
+        for field in fields {
+            self.add_field(message_name.clone(), field);
+        }
+
+        self.checkpoint(start);
+    }
+
+    /// Updates the cache with a field of a message, without a packet ID.
+    ///
+    /// This is used for fields which are known before the message's packet ID,
+    /// such as those loaded from a schema.
+    ///
+    /// If the field was already identified, it is only replaced
+    /// when the new identification has a higher confidence.
+    pub fn update_field(&mut self, message_name: String, field: MessageField) {
+        let start = self.events.len();
+        self.add_field(message_name, field);
+        self.checkpoint(start);
+    }
+
+    /// Adds a field to a message, replacing a less confident identification.
+    ///
+    /// Identifications are matched by field ID and type, as in `MessageField::same_field`.
+    fn add_field(&mut self, message_name: String, field: MessageField) {
+        let fields = self.messages.entry(message_name.clone()).or_default();
+        match fields.iter_mut().find(|known| known.same_field(&field)) {
+            Some(known) => {
+                if field.confidence() > known.confidence() {
+                    let previous = std::mem::replace(known, field.clone());
+                    self.push_event(CacheEvent::FieldReplaced {
+                        name: message_name,
+                        previous,
+                        field
+                    });
+                }
+            },
+            None => {
+                fields.push(field.clone());
+                self.push_event(CacheEvent::FieldAdded {
+                    name: message_name,
+                    field
+                });
+            }
+        }
+    }
+
+    /// Renames a packet, keeping its ID, fields, and other recorded data.
+    ///
+    /// The name is left unchanged if the new name is already in use.
+    ///
+    /// Returns whether the packet was renamed.
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> bool {
+        let known = self.name_map.contains_key(old_name) || self.messages.contains_key(old_name);
+        if !known || old_name == new_name {
+            return false;
+        }
+
+        if self.name_map.contains_key(new_name) || self.messages.contains_key(new_name) {
+            return false;
+        }
+
+        self.move_name(old_name, new_name);
+
+        let event = CacheEvent::NameChanged {
+            previous: old_name.to_string(),
+            name: new_name.to_string()
+        };
+        self.push_event(event.clone());
+        self.record(vec![event]);
+
+        true
+    }
+
+    /// Moves everything recorded under a packet name to another name.
+    fn move_name(&mut self, old_name: &str, new_name: &str) {
+        // Update the ID mappings.
+        if let Some(id) = self.name_map.remove(old_name) {
+            self.name_map.insert(new_name.to_string(), id);
+            self.id_map.insert(id, new_name.to_string());
+        }
+
+        for name in self.known_names.iter_mut().filter(|name| *name == old_name) {
+            *name = new_name.to_string();
+        }
+
+        // Move the data recorded under the old name.
+        if let Some(fields) = self.messages.remove(old_name) {
+            self.messages.insert(new_name.to_string(), fields);
+        }
+        if let Some(header) = self.headers.remove(old_name) {
+            self.headers.insert(new_name.to_string(), header);
+        }
+        if let Some(presence) = self.presence.remove(old_name) {
+            self.presence.insert(new_name.to_string(), presence);
+        }
+    }
+
+    /// Reverts the most recent change made to the cache.
+    ///
+    /// If the change added a new packet, the packet and its fields are removed entirely.
+    /// If it added a field to a known packet, only that field is removed,
+    /// and a replaced field is restored to its previous identification.
+    ///
+    /// Returns whether there was a change to revert.
+    pub fn undo_last(&mut self) -> bool {
+        let Some(events) = self.history.pop_back() else {
+            return false;
+        };
+
+        // Revert the events in the opposite order they occurred.
+        for event in events.iter().rev() {
+            match event {
+                CacheEvent::NameAdded { name, id } => {
+                    self.id_map.remove(id);
+                    self.name_map.remove(name);
+
+                    if let Some(index) = self.known_ids.iter().rposition(|known| known == id) {
+                        self.known_ids.remove(index);
+                    }
+                    if let Some(index) = self.known_names.iter().rposition(|known| known == name) {
+                        self.known_names.remove(index);
+                    }
+                },
+                CacheEvent::FieldAdded { name, field } => {
+                    let Some(fields) = self.messages.get_mut(name) else {
+                        continue;
+                    };
+
+                    fields.retain(|known| !known.same_field(field));
+                    if fields.is_empty() {
+                        self.messages.remove(name);
+                    }
+                },
+                CacheEvent::FieldReplaced { name, previous, field } => {
+                    let known = self.messages
+                        .get_mut(name)
+                        .and_then(|fields| fields.iter_mut().find(|known| known.same_field(field)));
+                    if let Some(known) = known {
+                        *known = previous.clone();
+                    }
+                },
+                CacheEvent::NameChanged { previous, name } => {
+                    self.move_name(name, previous);
+                },
+                CacheEvent::Undone { .. } => {}
+            }
+        }
+
+        self.push_event(CacheEvent::Undone { events });
+
+        true
+    }
+
+    /// Records the events added since `start` as a single change in the history.
+    fn checkpoint(&mut self, start: usize) {
+        if self.events.len() > start {
+            self.record(self.events[start..].to_vec());
+        }
+    }
+
+    /// Replaces this cache with a copy of another cache, then applies the given changes again.
+    ///
+    /// Packet IDs queried with `first_seen`, recorded headers, and field presence are kept.
+    /// No events are recorded for the applied changes.
+    pub(crate) fn rebase(&mut self, base: &Cache, changes: &[CacheEvent]) {
+        let previous = self.replace(base.clone());
+        self.seen_ids.extend(previous.seen_ids);
+        self.headers.extend(previous.headers);
+        self.presence.extend(previous.presence);
+
+        for change in changes {
+            match change {
+                CacheEvent::NameAdded { name, id } => self.add_name(name.clone(), *id),
+                CacheEvent::FieldAdded { name, field } | CacheEvent::FieldReplaced { name, field, .. } => {
+                    self.add_field(name.clone(), field.clone());
+                },
+                CacheEvent::NameChanged { previous, name } => {
+                    self.move_name(previous, name);
+                },
+                CacheEvent::Undone { .. } => {}
+            }
+        }
+
+        self.events.clear();
+        self.history.clear();
+    }
+
+    /// Adds a change to the history, dropping the oldest changes past the limit.
+    fn record(&mut self, events: Vec<CacheEvent>) {
+        self.history.push_back(events);
+        self.trim_history();
+    }
+
+    /// Sets the maximum number of changes which can be reverted with `undo_last`.
+    ///
+    /// If `None`, every change is kept. The oldest changes are dropped first.
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        self.history_limit = limit;
+        self.trim_history();
+    }
+
+    /// Drops the oldest changes in the history until it fits the limit.
+    fn trim_history(&mut self) {
+        if let Some(limit) = self.history_limit {
+            let excess = self.history.len().saturating_sub(limit);
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Compares this cache against another cache.
+    ///
+    /// The differences are described relative to the other cache,
+    /// so `added_names` contains names which only this cache knows.
+    ///
+    /// Fields are matched by ID and type, as in `MessageField::same_field`,
+    /// so a field identified with another type is reported as added.
+    pub fn diff(&self, other: &Cache) -> CacheDiff {
+        let mut diff = CacheDiff::default();
+
+        for name in &self.known_names {
+            if !other.name_known(name) {
+                diff.added_names.push(name.clone());
+            }
+        }
+
+        for name in &other.known_names {
+            if !self.name_known(name) {
+                diff.removed_names.push(name.clone());
+            }
+        }
+
+        // Compare the fields of each message.
+        for (name, fields) in &self.messages {
+            let previous = other.fields(name).unwrap_or_default();
+
+            for field in fields {
+                match previous.iter().find(|known| known.same_field(field)) {
+                    Some(known) => {
+                        if known.field_name != field.field_name {
+                            diff.changed_fields
+                                .entry(name.clone())
+                                .or_default()
+                                .push(FieldChange {
+                                    previous: known.clone(),
+                                    current: field.clone()
+                                });
+                        }
+                    },
+                    None => {
+                        diff.added_fields
+                            .entry(name.clone())
+                            .or_default()
+                            .push(field.clone());
+                    }
+                }
+            }
+        }
+
+        diff
+    }
+
+    /// Serializes the cache as pretty-printed JSON.
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Removes all changes which have not yet been sent to listeners.
+    pub(crate) fn take_events(&mut self) -> Vec<CacheEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Records a change which has not yet been sent to listeners.
+    fn push_event(&mut self, event: CacheEvent) {
+        self.events.push(event);
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Returns the counter which is incremented whenever the cache changes.
+    pub(crate) fn generation(&self) -> Arc<AtomicU64> {
+        self.generation.clone()
+    }
+
+    /// Replaces the contents of this cache with another cache.
+    ///
+    /// Unlike assigning the cache, this keeps the cache's generation counter,
+    /// so script realms which read it see that the cache changed.
+    pub(crate) fn replace(&mut self, other: Cache) -> Cache {
+        let generation = self.generation.clone();
+        let previous = std::mem::replace(self, other);
+
+        self.generation = generation;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+
+        previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a field with the given name, type, and ID.
+    fn field(name: &str, field_type: &str, id: u16) -> MessageField {
+        MessageField {
+            field_name: name.to_string(),
+            field_type: field_type.to_string(),
+            field_id: id,
+            confidence: None
+        }
+    }
+
+    #[test]
+    fn diff_matches_fields_by_id_and_type() {
+        let mut before = Cache::default();
+        before.update("Chat".to_string(), 9, field("text", "string", 1));
+        let mut sender = field("sender", "uint32", 2);
+        sender.confidence = Some(0.5);
+        before.update("Chat".to_string(), 9, sender);
+
+        // Field 1 is also identified as bytes, and field 2 is renamed with more confidence.
+        let mut after = before.clone();
+        after.update("Chat".to_string(), 9, field("payload", "bytes", 1));
+        after.update("Chat".to_string(), 9, field("author", "uint32", 2));
+
+        let diff = after.diff(&before);
+        assert_eq!(diff.added_fields["Chat"], [field("payload", "bytes", 1)]);
+
+        let [change] = diff.changed_fields["Chat"].as_slice() else {
+            panic!("expected one change, found {:?}", diff.changed_fields);
+        };
+        assert_eq!(change.previous.field_name, "sender");
+        assert_eq!(change.current.field_name, "author");
+    }
+
+    #[test]
+    fn field_known_can_require_a_type() {
+        let mut cache = Cache::default();
+        cache.update("Move".to_string(), 3, field("speed", "float", 4));
+
+        assert!(cache.field_known("Move", 4, None));
+        assert!(cache.field_known("Move", 4, Some("float")));
+        assert!(!cache.field_known("Move", 4, Some("double")));
+        assert!(!cache.field_known("Move", 5, None));
+        assert!(!cache.field_known("Jump", 4, None));
+    }
+
+    #[test]
+    fn undoing_a_new_packet_removes_it_entirely() {
+        let mut cache = Cache::default();
+        cache.update("Trade".to_string(), 21, field("item", "uint32", 1));
+
+        assert!(cache.undo_last());
+        assert!(!cache.name_known("Trade"));
+        assert_eq!(cache.name_of(21), None);
+        assert!(cache.fields("Trade").is_none());
+    }
+
+    #[test]
+    fn undoing_a_new_field_keeps_the_packet() {
+        let mut cache = Cache::default();
+        cache.update("Trade".to_string(), 21, field("item", "uint32", 1));
+        cache.update("Trade".to_string(), 21, field("amount", "uint32", 2));
+
+        assert!(cache.undo_last());
+        assert_eq!(cache.id_of("Trade"), Some(21));
+        assert_eq!(cache.fields("Trade").unwrap(), [field("item", "uint32", 1)]);
+    }
+
+    #[test]
+    fn undoing_a_replacement_restores_the_previous_field() {
+        let mut guess = field("price", "uint64", 3);
+        guess.confidence = Some(0.3);
+
+        let mut cache = Cache::default();
+        cache.update("Trade".to_string(), 21, guess.clone());
+        cache.update("Trade".to_string(), 21, field("cost", "uint64", 3));
+        assert_eq!(cache.fields("Trade").unwrap()[0].field_name, "cost");
+
+        assert!(cache.undo_last());
+        assert_eq!(cache.fields("Trade").unwrap(), [guess]);
+    }
+
+    #[test]
+    fn undoing_a_rename_restores_the_old_name() {
+        let mut cache = Cache::default();
+        cache.update("Trade".to_string(), 21, field("item", "uint32", 1));
+        assert!(cache.rename("Trade", "Exchange"));
+
+        assert!(cache.undo_last());
+        assert_eq!(cache.name_of(21), Some("Trade"));
+        assert!(cache.field_known("Trade", 1, None));
+        assert!(!cache.name_known("Exchange"));
+    }
+
+    #[test]
+    fn undo_is_not_itself_undone() {
+        let mut cache = Cache::default();
+        assert!(!cache.undo_last());
+
+        cache.update_name("Trade".to_string(), 21);
+        assert!(cache.undo_last());
+
+        // The undo is reported to listeners, but cannot be reverted.
+        assert!(!cache.undo_last());
+        assert!(matches!(cache.take_events().last(), Some(CacheEvent::Undone { .. })));
+    }
+
+    #[test]
+    fn history_is_bounded_by_the_limit() {
+        let mut cache = Cache::default();
+        cache.set_history_limit(Some(2));
+
+        for (id, name) in [(1, "First"), (2, "Second"), (3, "Third")] {
+            cache.update_name(name.to_string(), id);
+        }
+
+        // Only the two most recent changes can be reverted.
+        assert!(cache.undo_last());
+        assert!(cache.undo_last());
+        assert!(!cache.undo_last());
+        assert_eq!(cache.known_names(), ["First"]);
+    }
+
+    /// Writes the given contents to a temporary file, loads it as a cache, and removes the file.
+    fn load(label: &str, contents: &str) -> Result<Cache, BiscuitError> {
+        let path = std::env::temp_dir().join(format!("biscuit-cache-{}-{}.json", label, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+
+        let cache = Cache::load_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        cache
+    }
+
+    #[test]
+    fn cache_without_version_loads_with_defaults() {
+        let cache = load("v0", r#"{
+            "known_names": ["PingReq"],
+            "known_ids": [5],
+            "id_map": { "5": "PingReq" },
+            "name_map": { "PingReq": 5 },
+            "messages": {}
+        }"#).unwrap();
+
+        assert_eq!(cache.version, Cache::VERSION);
+        assert_eq!(cache.name_of(5), Some("PingReq"));
+        assert!(cache.header("PingReq").is_none());
+    }
+
+    #[test]
+    fn cache_from_newer_version_is_rejected() {
+        let result = load("next", &format!(r#"{{ "version": {} }}"#, Cache::VERSION + 1));
+
+        assert!(matches!(
+            result,
+            Err(BiscuitError::CacheVersion { found, supported }) if found == Cache::VERSION + 1 && supported == Cache::VERSION
+        ));
+    }
+
+    #[test]
+    fn saved_cache_loads_back() {
+        let mut cache = Cache::default();
+        cache.update("Inventory".to_string(), 17, field("slots", "repeated uint32", 2));
+
+        let loaded = load("saved", &cache.to_json_pretty().unwrap()).unwrap();
+        assert_eq!(loaded.version, Cache::VERSION);
+        assert_eq!(loaded.id_of("Inventory"), Some(17));
+        assert_eq!(loaded.fields("Inventory").unwrap(), [field("slots", "repeated uint32", 2)]);
+    }
+}
@@ -4,6 +4,7 @@ use serde::{Serialize, Deserialize};
 ///
 /// This does not include any programmable logic.
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
 pub struct Config {
     /// The path to a directory containing matching script files.
     /// The specific path needs to be readable and writable.
@@ -17,21 +18,322 @@ pub struct Config {
     
     /// The path to a file containing environment variables.
     /// 
-    /// These are the only variables that will be accessible to scripts.
+    /// Unless `inherit_process_env` is set, these are the only variables that will be accessible to scripts.
     /// 
     /// If left blank, no environment variables will be loaded.
     /// 
     /// # Default
     /// 
     /// `.env`
-    pub environment_file: String
+    pub environment_file: String,
+
+    /// Whether the process's environment variables are provided to scripts.
+    ///
+    /// Variables in the environment file take precedence over the process's variables.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub inherit_process_env: bool,
+
+    /// Whether `${NAME}` references in the environment file are expanded.
+    ///
+    /// References are resolved against the other variables in the file,
+    /// then the process environment.
+    ///
+    /// Disable this to keep literal `$` characters in values.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub interpolate_env: bool,
+
+    /// Whether `input` may be called from a thread other than the main thread.
+    ///
+    /// When enabled, `input` must instead be called from the same thread
+    /// that called `initialize`, since script contexts are not thread-safe.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub allow_any_thread: bool,
+
+    /// The number of worker threads to run comparers on.
+    ///
+    /// Each worker owns its own script contexts, and comparers are
+    /// split evenly between workers.
+    ///
+    /// If set to `0`, all comparers are run on the calling thread.
+    ///
+    /// # Default
+    ///
+    /// `0`
+    pub worker_threads: usize,
+
+    /// The seed used for the `random` function provided to scripts.
+    ///
+    /// Each script is given its own generator, seeded with this value.
+    ///
+    /// If left blank, the generator is seeded randomly.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub rng_seed: Option<u64>,
+
+    /// Whether a packet is rejected when its header fails to decode.
+    ///
+    /// When disabled, an empty header is provided to scripts instead,
+    /// since headers often contain framing bytes which are not protobuf-encoded.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub strict_header: bool,
+
+    /// The maximum number of iterations any single loop in a script may run.
+    ///
+    /// Unlike a timeout, this is deterministic, so scripts are aborted
+    /// at the same point on every machine.
+    ///
+    /// This limit applies to each loop separately, and is not a budget for the whole script.
+    /// Work done outside of loops is not limited by this, and the script engine
+    /// does not count individual instructions. Recursion is limited separately
+    /// by `max_recursion_depth` and `max_stack_size`.
+    ///
+    /// If left blank, loops are not limited.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub max_loop_iterations: Option<u64>,
+
+    /// The maximum depth of nested function calls in a script.
+    ///
+    /// A call which goes deeper throws a `RangeError`, which the script may catch.
+    ///
+    /// If left blank, the script engine's default of 512 is used.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub max_recursion_depth: Option<usize>,
+
+    /// The maximum number of values on a script's stack.
+    ///
+    /// A script which exceeds this throws a `RangeError`, which the script may catch.
+    ///
+    /// If left blank, the script engine's default of 10240 is used.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub max_stack_size: Option<usize>,
+
+    /// The path to a `.proto` file containing already-known messages.
+    ///
+    /// The fields of each message are added to the cache when the library is initialized,
+    /// so scripts only need to identify the remaining fields.
+    ///
+    /// If left blank, no schema is loaded.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub proto_schema: Option<String>,
+
+    /// Whether scripts update a scratch cache instead of the real cache.
+    ///
+    /// The scratch cache is a copy of the real cache with the scripts' changes applied,
+    /// and is never merged into it. It is copied again whenever the real cache changes,
+    /// such as with `undo` or `rename_packet`. Its contents can be fetched with `dry_run_results`.
+    ///
+    /// Cache listeners are not notified of changes made by scripts in this mode.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub dry_run: bool,
+
+    /// Whether the remaining scripts are skipped once a script matches a packet.
+    ///
+    /// A script matches a packet by returning `true` from `compare`.
+    ///
+    /// When worker threads are used, each worker stops independently,
+    /// so scripts on other workers may still run.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub stop_on_first_match: bool,
+
+    /// Whether `identify` rejects fields with an invalid type.
+    ///
+    /// Invalid types are always logged as a warning.
+    /// When enabled, `identify` additionally throws an error.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub strict_field_types: bool,
+
+    /// Whether the most recent header of each named packet is kept in the cache.
+    ///
+    /// Headers can be read with `Cache::header`.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub retain_headers: bool,
+
+    /// The most verbose level which scripts may log at.
+    ///
+    /// This is one of `off`, `error`, `warn`, `info`, `debug`, or `trace`.
+    /// Messages logged by scripts below this level are discarded.
+    ///
+    /// This does not affect `console.log`.
+    ///
+    /// # Default
+    ///
+    /// `trace`
+    pub script_log_level: String,
+
+    /// The maximum size, in bytes, of a header or packet provided to `input`.
+    ///
+    /// Larger buffers are rejected before being decoded,
+    /// so malformed captures cannot exhaust memory.
+    ///
+    /// If left blank, buffers of any size are decoded.
+    ///
+    /// # Default
+    ///
+    /// `Some(16777216)` (16 MiB)
+    pub max_packet_size: Option<usize>,
+
+    /// Whether scripts are loaded as ES modules instead of classic scripts.
+    ///
+    /// This allows scripts to use `import` and `export`.
+    /// Imports are resolved relative to `script_path`.
+    ///
+    /// Exports of the script, such as `compare` and `PACKET_NAME`, are used in place of globals.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub module_scripts: bool,
+
+    /// The path to a script which is run once after all comparers have loaded.
+    ///
+    /// The script has access to the same functions as comparers,
+    /// and is given the packet names of every loaded comparer as `LOADED_NAMES`.
+    /// If the script throws, initialization fails.
+    ///
+    /// This should be kept outside of `script_path`, or it will also be loaded as a comparer.
+    ///
+    /// If left blank, no script is run.
+    ///
+    /// # Default
+    ///
+    /// `None`
+    pub post_load_script: Option<String>,
+
+    /// The maximum number of levels of nested messages converted for scripts.
+    ///
+    /// Messages nested deeper than this are provided to scripts as `bytes` instead,
+    /// so pathological packets cannot exhaust the stack. A warning is logged when this happens.
+    /// This also applies to headers recorded with `retain_headers`.
+    ///
+    /// This only limits the conversion for scripts, not protoshark's own decoding,
+    /// which still reads nested messages at any depth.
+    ///
+    /// If left blank, messages are converted at any depth.
+    ///
+    /// # Default
+    ///
+    /// `Some(64)`
+    pub max_message_depth: Option<usize>,
+
+    /// Whether nested messages are given to scripts as messages with the same methods as `data`.
+    ///
+    /// When disabled, a nested message is a plain object whose `inner` property
+    /// maps each field ID to its value.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub nested_message_methods: bool,
+
+    /// Whether the cache counts how often each field of a named packet is present.
+    ///
+    /// Counts can be read with `Cache::presence`, and help tell optional fields from required ones.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub track_field_presence: bool,
+
+    /// Whether scripts may `require` files outside of their own directory.
+    ///
+    /// When disabled, paths which escape the script directory, such as `../secrets.json`, are rejected.
+    /// Enable this to share helpers from a sibling directory.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub allow_require_outside_root: bool,
+
+    /// Whether initializing fails if any script fails to load.
+    ///
+    /// When disabled, scripts which fail to parse or evaluate are logged and skipped.
+    /// When enabled, the error lists every script which failed to load.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub strict_init: bool,
+
+    /// The maximum number of changes kept in the cache's history.
+    ///
+    /// This bounds both the changes which can be reverted with `undo`
+    /// and the events returned by `history`. The oldest changes are dropped first.
+    ///
+    /// If left blank, every change is kept.
+    ///
+    /// # Default
+    ///
+    /// `Some(1024)`
+    pub history_limit: Option<usize>
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             script_path: "scripts".to_string(),
-            environment_file: ".env".to_string()
+            environment_file: ".env".to_string(),
+            inherit_process_env: false,
+            interpolate_env: false,
+            allow_any_thread: false,
+            worker_threads: 0,
+            rng_seed: None,
+            strict_header: false,
+            max_loop_iterations: None,
+            max_recursion_depth: None,
+            max_stack_size: None,
+            proto_schema: None,
+            dry_run: false,
+            stop_on_first_match: false,
+            strict_field_types: false,
+            retain_headers: false,
+            script_log_level: "trace".to_string(),
+            max_packet_size: Some(16 * 1024 * 1024),
+            module_scripts: false,
+            post_load_script: None,
+            max_message_depth: Some(64),
+            nested_message_methods: false,
+            track_field_presence: false,
+            allow_require_outside_root: false,
+            strict_init: false,
+            history_limit: Some(1024)
         }
     }
 }
\ No newline at end of file
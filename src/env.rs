@@ -0,0 +1,51 @@
+use log::warn;
+use crate::matcher::Env;
+
+/// Expands `${NAME}` references in the values of the environment variables.
+///
+/// References are resolved against the other variables first, then the process environment.
+/// Unresolved references are left as-is.
+pub(crate) fn interpolate(variables: &Env) -> Env {
+    variables
+        .iter()
+        .map(|(key, value)| (key.clone(), expand(key, value, variables)))
+        .collect()
+}
+
+/// Expands all references in a single value.
+fn expand(key: &str, value: &str, variables: &Env) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+
+        // Find the end of the reference.
+        let reference = &rest[start + 2..];
+        let Some(end) = reference.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &reference[..end];
+
+        // A variable cannot reference itself, but can extend the process environment.
+        let resolved = match name == key {
+            true => None,
+            false => variables.get(name).cloned()
+        };
+        let resolved = resolved.or_else(|| std::env::var(name).ok());
+
+        match resolved {
+            Some(resolved) => result.push_str(&resolved),
+            None => {
+                warn!("Unresolved reference '${{{}}}' in environment variable '{}'", name, key);
+                result.push_str(&rest[start..start + end + 3]);
+            }
+        }
+
+        rest = &reference[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
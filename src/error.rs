@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors which can be returned by the library.
+///
+/// This can be converted into an `anyhow::Error` with `?`.
+#[derive(Debug, Error)]
+pub enum BiscuitError {
+    /// The configured script folder does not exist.
+    #[error("script folder does not exist: {}", .0.to_string_lossy())]
+    ScriptPathMissing(PathBuf),
+
+    /// The environment file could not be loaded.
+    #[error("failed to load environment file: {0}")]
+    EnvParse(String),
+
+    /// Part of a packet could not be decoded.
+    #[error("failed to decode {part}: {message}")]
+    Decode {
+        /// The part of the packet which failed to decode.
+        part: &'static str,
+
+        /// The reason the packet failed to decode.
+        message: String
+    },
+
+    /// Part of a packet exceeded `Config::max_packet_size`.
+    #[error("{part} is too large: {size} bytes exceeds the limit of {limit} bytes")]
+    PacketTooLarge {
+        /// The part of the packet which was too large.
+        part: &'static str,
+
+        /// The size of the part, in bytes.
+        size: usize,
+
+        /// The configured size limit, in bytes.
+        limit: usize
+    },
+
+    /// A saved cache was written by a newer version of the library.
+    #[error("cache version {found} is newer than the supported version {supported}")]
+    CacheVersion {
+        /// The version of the saved cache.
+        found: u32,
+
+        /// The newest version this library can load.
+        supported: u32
+    },
+
+    /// The function was called off of the main thread.
+    #[error("input can only be called on the main thread")]
+    NotMainThread,
+
+    /// The function was called off of the thread which initialized the library.
+    #[error("input can only be called on the initializing thread")]
+    NotInitializingThread,
+
+    /// A script failed to load or run.
+    #[error("script '{name}' failed: {source}")]
+    Script {
+        /// The name of the script.
+        name: String,
+
+        /// The error raised by the script.
+        source: Box<dyn std::error::Error + Send + Sync>
+    },
+
+    /// Any other error.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error)
+}
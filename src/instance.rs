@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use anyhow::anyhow;
+use dotenv_parser::parse_dotenv;
+use is_main_thread::is_main_thread;
+use crate::cache::{Cache, CacheEvent};
+use crate::config::Config;
+use crate::env;
+use crate::error::BiscuitError;
+use crate::matcher::{Direction, Env, FeedOutcome, MatchResult, Matcher, Packet, ProbeResult, ScriptError, WarmupSummary};
+use crate::message::DecodedMessage;
+use crate::metrics::Metrics;
+#[cfg(feature = "pcap")]
+use crate::pcap::ReplayStats;
+use crate::ProtoMessage;
+
+/// An independent instance of the library.
+///
+/// Each instance has its own configuration, scripts, and cache,
+/// so multiple protocols can be analyzed side by side.
+///
+/// The free functions in this crate use a shared default instance.
+///
+/// Cloning an instance returns a handle to the same matcher.
+#[derive(Clone, Debug)]
+pub struct Biscuit {
+    matcher: Arc<Mutex<Matcher>>
+}
+
+impl Biscuit {
+    /// Creates a new, uninitialized instance.
+    pub fn new() -> Self {
+        Biscuit {
+            matcher: Arc::new(Mutex::new(Matcher::new()))
+        }
+    }
+
+    /// Locks the instance's matcher.
+    ///
+    /// If a previous call panicked while holding the lock, the matcher is still returned,
+    /// so one failure does not disable the instance for the rest of the process.
+    fn lock(&self) -> MutexGuard<'_, Matcher> {
+        self.matcher.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Checks if the current thread is allowed to use the matcher.
+    fn check_thread(matcher: &Matcher) -> Result<(), BiscuitError> {
+        if matcher.config.allow_any_thread {
+            if !matcher.on_initializing_thread() {
+                return Err(BiscuitError::NotInitializingThread);
+            }
+        } else {
+            let is_main = is_main_thread().unwrap_or(true);
+            if !is_main {
+                return Err(BiscuitError::NotMainThread);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the instance.
+    ///
+    /// This method should be called before any packets are processed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use biscuit::Biscuit;
+    /// use biscuit::config::Config;
+    ///
+    /// let instance = Biscuit::new();
+    /// instance.initialize(Config::default())
+    ///     .expect("invalid configuration specified");
+    /// ```
+    pub fn initialize(&self, config: Config) -> Result<(), BiscuitError> {
+        let mut matcher = self.lock();
+
+        // Load all matcher scripts.
+        let path = config.script_path.clone();
+        let path = Path::new(&path);
+        if !path.exists() {
+            return Err(BiscuitError::ScriptPathMissing(path.to_path_buf()));
+        }
+
+        let variables = Self::load_env(&config)?;
+
+        // Check the configuration before replacing the current one.
+        Matcher::validate_config(&config)?;
+
+        // Initialize the matcher.
+        matcher.config = Arc::new(config);
+        matcher.initialize(path, variables)?;
+
+        Ok(())
+    }
+
+    /// Initializes the instance with scripts provided in memory, keyed by file name.
+    ///
+    /// This allows scripts to be embedded in the binary, such as with `include_str!`.
+    /// `Config::script_path` is not read, but modules loaded with `require` or `import`
+    /// are still resolved against the working directory.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::collections::HashMap;
+    /// use biscuit::Biscuit;
+    /// use biscuit::config::Config;
+    ///
+    /// let source = "const PACKET_NAME = 'Login'; function compare(id, header, data) {}";
+    /// let sources = HashMap::from([("login.js".to_string(), source.to_string())]);
+    ///
+    /// let instance = Biscuit::new();
+    /// instance.initialize_from_sources(Config::default(), sources)
+    ///     .expect("invalid configuration specified");
+    /// ```
+    pub fn initialize_from_sources(
+        &self,
+        config: Config,
+        sources: HashMap<String, String>
+    ) -> Result<(), BiscuitError> {
+        let mut matcher = self.lock();
+        let variables = Self::load_env(&config)?;
+
+        // Check the configuration before replacing the current one.
+        Matcher::validate_config(&config)?;
+
+        // Initialize the matcher.
+        matcher.config = Arc::new(config);
+        matcher.initialize_from_sources(sources, variables)?;
+
+        Ok(())
+    }
+
+    /// Loads the environment variables provided to scripts.
+    fn load_env(config: &Config) -> Result<Option<Env>, BiscuitError> {
+        // Seed the environment variables from the process, if enabled.
+        let mut variables = match config.inherit_process_env {
+            true => Some(std::env::vars_os()
+                .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+                .collect::<Env>()),
+            false => None
+        };
+
+        // Try loading the environment file.
+        // These variables take precedence over the process's variables.
+        let env_file = &config.environment_file;
+        let file = Path::new(env_file);
+
+        if file.exists() {
+            let content = std::fs::read_to_string(file)
+                .map_err(|error| BiscuitError::EnvParse(error.to_string()))?;
+            let map = match parse_dotenv(&content) {
+                Ok(map) if config.interpolate_env => Some(env::interpolate(&map)),
+                Ok(map) => Some(map),
+                Err(error) => {
+                    log::warn!("failed to parse environment file: {}", error);
+                    None
+                }
+            };
+
+            if let Some(map) = map {
+                variables.get_or_insert_with(Env::new).extend(map);
+            }
+        }
+
+        Ok(variables)
+    }
+
+    /// Processes the input data.
+    /// 
+    /// # Notice
+    /// 
+    /// This should **only** be called on the main thread.
+    ///
+    /// If `Config::allow_any_thread` is set, this should instead **only** be
+    /// called on the thread which called `initialize`.
+    ///
+    /// If the direction of the packet is not known, use `Direction::Unknown`.
+    ///
+    /// Returns the packet's name if it was newly identified by this packet.
+    ///
+    /// # Decryption
+    ///
+    /// A script which sets `DECRYPTS = true` and exports `decrypt(id, data)` is given the data
+    /// of every packet before it is decoded, and returns the decrypted data as an `ArrayBuffer`,
+    /// or `undefined` to leave it unchanged. The decrypted data is then given to every script.
+    ///
+    /// If multiple scripts set `DECRYPTS`, only the first by file name is used.
+    /// Packets are only decrypted when they are input, so `decrypt` may keep state between packets.
+    pub fn input(
+        &self,
+        id: u16,
+        direction: Direction,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<Option<String>, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        // Compare the data.
+        matcher.compare(id, direction, header, data)
+    }
+
+    /// Processes data which has already been decoded.
+    ///
+    /// This avoids decoding the packet again when the caller has already parsed it.
+    /// Since the encoded bytes are not available, scripts cannot read raw field bytes.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn input_decoded(
+        &self,
+        id: u16,
+        direction: Direction,
+        header: ProtoMessage,
+        data: ProtoMessage
+    ) -> Result<Option<String>, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        // Compare the data.
+        let header = DecodedMessage::new(header, None);
+        let data = DecodedMessage::new(data, None);
+        matcher.compare_decoded(id, direction, header, data)
+    }
+
+    /// Processes the input data, describing why it could not be decoded.
+    ///
+    /// This is intended for debugging captures which do not decode.
+    /// Unlike `input`, the packet is decoded even if no script would be run,
+    /// and a header which cannot be decoded is reported even if `Config::strict_header` is not set.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn feed_raw(
+        &self,
+        id: u16,
+        direction: Direction,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<FeedOutcome, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        matcher.feed_raw(id, direction, header, data)
+    }
+
+    /// Processes multiple packets in order.
+    ///
+    /// This only locks the matcher once, which is faster when replaying a capture.
+    ///
+    /// The result of each packet is returned in the same order as the input.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn input_batch(
+        &self,
+        packets: &[Packet]
+    ) -> Result<Vec<Result<Option<String>, BiscuitError>>, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        // Compare each packet.
+        let results = packets
+            .iter()
+            .map(|packet| matcher.compare(
+                packet.id, packet.direction,
+                &packet.header, &packet.data
+            ))
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Starts a thread which owns the matcher, and feeds it packets over a channel.
+    ///
+    /// The scripts are loaded on the new thread, which is the only thread that may then provide packets.
+    /// `Config::allow_any_thread` is enabled to allow this.
+    ///
+    /// Each packet sent to the returned sender produces one result on the returned receiver,
+    /// in the same order. Dropping the sender unloads the scripts and stops the thread.
+    ///
+    /// This allows packets to be provided from async code without blocking on the matcher.
+    ///
+    /// Returns an error if the scripts fail to load.
+    pub fn spawn_worker(
+        &self,
+        mut config: Config
+    ) -> Result<(Sender<Packet>, Receiver<MatchResult>), BiscuitError> {
+        config.allow_any_thread = true;
+
+        let (packets, on_packet) = channel::<Packet>();
+        let (results, on_result) = channel::<MatchResult>();
+        let (loaded, on_loaded) = channel::<Result<(), BiscuitError>>();
+
+        let instance = self.clone();
+        std::thread::Builder::new()
+            .name("biscuit-input".to_string())
+            .spawn(move || {
+                // Load the scripts on this thread.
+                let result = instance.initialize(config);
+                let failed = result.is_err();
+                let _ = loaded.send(result);
+                if failed {
+                    return;
+                }
+
+                // Process packets until the sender is dropped.
+                for packet in on_packet {
+                    let result = instance.input(
+                        packet.id, packet.direction,
+                        &packet.header, &packet.data
+                    );
+
+                    if results.send(MatchResult { id: packet.id, result }).is_err() {
+                        break;
+                    }
+                }
+
+                // The scripts must be unloaded on the thread which loaded them.
+                if let Err(error) = instance.shutdown() {
+                    log::warn!("Failed to shut down input worker: {}", error);
+                }
+            })
+            .map_err(|error| BiscuitError::Other(error.into()))?;
+
+        // Wait for the scripts to load.
+        match on_loaded.recv() {
+            Ok(result) => result?,
+            Err(_) => return Err(BiscuitError::Other(anyhow!("input worker exited while loading scripts")))
+        };
+
+        Ok((packets, on_result))
+    }
+
+    /// Replays a pcap or pcapng capture file through the matcher.
+    ///
+    /// The extractor is given the captured bytes of each packet, including any link-layer headers,
+    /// and returns the packet to provide to the matcher. Packets for which it returns `None` are skipped.
+    ///
+    /// This requires the `pcap` feature.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    #[cfg(feature = "pcap")]
+    pub fn replay_pcap<F>(&self, path: impl AsRef<Path>, extractor: F) -> Result<ReplayStats, BiscuitError>
+    where
+        F: FnMut(&[u8]) -> Option<Packet>
+    {
+        crate::pcap::replay(self, path.as_ref(), extractor)
+    }
+
+    /// Runs every script against sample packets, without keeping any identifications.
+    ///
+    /// Each sample is a packet ID, header, and data.
+    ///
+    /// This can be used to check that scripts run without errors before processing live traffic.
+    /// Samples are not passed to a script's `decrypt` function, so they must already be decrypted.
+    /// Samples which cannot be decoded are skipped and listed in the summary.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn warmup(&self, samples: &[(u16, Vec<u8>, Vec<u8>)]) -> Result<WarmupSummary, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        matcher.warmup(samples)
+    }
+
+    /// Forces every script to compare a packet, and reports the outcome of each.
+    ///
+    /// Unlike `input`, scripts are run even if the packet is known to belong to another script.
+    /// Any identifications made are discarded.
+    /// The data is not passed to a script's `decrypt` function, so it must already be decrypted.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn probe(&self, id: u16, header: &[u8], data: &[u8]) -> Result<Vec<ProbeResult>, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        matcher.probe(id, header, data)
+    }
+
+    /// Renders a one-line summary of a packet, using the `format` function of its script.
+    ///
+    /// The packet's name must already be known, and the script which identifies
+    /// that name must export `format(id, data)`. Otherwise, `None` is returned.
+    /// The data is not passed to a script's `decrypt` function, so it must already be decrypted.
+    ///
+    /// # Notice
+    ///
+    /// This has the same thread requirements as `input`.
+    pub fn format_packet(&self, id: u16, header: &[u8], data: &[u8]) -> Result<Option<String>, BiscuitError> {
+        // Fetch the matcher.
+        let mut matcher = self.lock();
+
+        // Check if we are on the correct thread.
+        Self::check_thread(&matcher)?;
+
+        matcher.format(id, header, data)
+    }
+
+    /// Fetches the cache.
+    ///
+    /// This returns a clone.
+    pub fn cache(&self) -> Cache {
+        let matcher = self.lock();
+        let cache = matcher.cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+        cache.clone()
+    }
+
+    /// Renames a packet in the cache, keeping its ID and fields.
+    ///
+    /// This is used to correct a packet name which was guessed wrong.
+    /// The name is left unchanged if the new name is already in use.
+    ///
+    /// Returns whether the packet was renamed.
+    pub fn rename_packet(&self, old_name: &str, new_name: &str) -> bool {
+        let mut matcher = self.lock();
+        matcher.rename_packet(old_name, new_name)
+    }
+
+    /// Reverts the most recent change made to the cache.
+    ///
+    /// If the change added a new packet, the packet and its fields are removed entirely.
+    /// If it added a field to a known packet, only that field is removed.
+    /// Changes can be reverted repeatedly, newest first.
+    /// Only the most recent `Config::history_limit` changes can be reverted.
+    ///
+    /// Returns whether there was a change to revert.
+    pub fn undo(&self) -> bool {
+        let mut matcher = self.lock();
+        matcher.undo()
+    }
+
+    /// Fetches the most recent changes made to the cache, in the order they were made.
+    ///
+    /// Reverted changes are kept, followed by a `CacheEvent::Undone` event.
+    /// At most `Config::history_limit` events are kept.
+    pub fn history(&self) -> Vec<CacheEvent> {
+        let matcher = self.lock();
+        matcher.history().iter().cloned().collect()
+    }
+
+    /// Adds the messages of a compiled `FileDescriptorSet` to the cache.
+    ///
+    /// This is useful when part of the schema is already known.
+    /// The names, IDs, and types of every field are added with full confidence.
+    /// Nested messages are named with their parents' names, such as `Outer.Inner`.
+    /// The values of enum fields are named as if by `registerEnum`.
+    ///
+    /// This requires the `descriptor` feature.
+    ///
+    /// # Notice
+    ///
+    /// Enum value names are cleared by `initialize`, so this should be called afterwards.
+    #[cfg(feature = "descriptor")]
+    pub fn load_descriptor(&self, descriptor: &[u8]) -> Result<(), BiscuitError> {
+        let mut matcher = self.lock();
+        matcher.identify_from_schema(descriptor)?;
+
+        Ok(())
+    }
+
+    /// Fetches the cache updated by scripts while `Config::dry_run` is set.
+    ///
+    /// Use `Cache::diff` against `cache` to see what the scripts would have identified.
+    /// If `Config::dry_run` is not set, the returned cache is empty.
+    ///
+    /// This returns a clone.
+    pub fn dry_run_results(&self) -> Cache {
+        let matcher = self.lock();
+        let Some(cache) = matcher.dry_run_results() else {
+            return Cache::default();
+        };
+
+        let cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.clone()
+    }
+
+    /// Fetches the cache as pretty-printed JSON.
+    pub fn cache_json(&self) -> Result<String, BiscuitError> {
+        let matcher = self.lock();
+        let cache = matcher.cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+        cache
+            .to_json_pretty()
+            .map_err(|error| BiscuitError::Other(error.into()))
+    }
+
+    /// Fetches the most recent errors raised by each script.
+    ///
+    /// This returns a clone.
+    pub fn recent_errors(&self) -> Vec<ScriptError> {
+        let matcher = self.lock();
+        matcher.recent_errors()
+    }
+
+    /// Fetches the matcher's metrics.
+    ///
+    /// This returns a clone.
+    pub fn metrics(&self) -> Metrics {
+        let matcher = self.lock();
+        matcher.metrics().clone()
+    }
+
+    /// Fetches the number of times each unknown packet ID was not matched by any script.
+    ///
+    /// IDs with the highest counts are the best candidates for new scripts.
+    ///
+    /// This returns a clone.
+    pub fn unmatched_ids(&self) -> HashMap<u16, u64> {
+        let matcher = self.lock();
+        matcher.unmatched_ids().clone()
+    }
+
+    /// Renders a human-readable summary of the loaded scripts, cache, and metrics.
+    ///
+    /// This is intended for status reports and logs.
+    pub fn describe(&self) -> String {
+        let matcher = self.lock();
+        matcher.describe()
+    }
+
+    /// Resets the matcher's metrics to zero.
+    ///
+    /// The cache and loaded scripts are left unchanged.
+    pub fn reset_metrics(&self) {
+        let mut matcher = self.lock();
+        matcher.reset_metrics();
+    }
+
+    /// Unloads all scripts, running their `teardown` functions.
+    ///
+    /// The cache is left unchanged, and scripts can be loaded again with `initialize`.
+    ///
+    /// # Notice
+    ///
+    /// This must be called on the same thread as `initialize`,
+    /// since the script contexts are owned by that thread.
+    /// If the instance is instead dropped on another thread, `teardown` functions are not run.
+    pub fn shutdown(&self) -> Result<(), BiscuitError> {
+        let mut matcher = self.lock();
+        matcher.shutdown()
+    }
+
+    /// Registers a function which is called whenever the cache is updated.
+    ///
+    /// The function is called once for every change, after each packet is compared.
+    ///
+    /// # Notice
+    ///
+    /// The function is called while the library is locked,
+    /// so it must not call any other method on this instance.
+    pub fn on_cache_update<F>(&self, listener: F)
+    where
+        F: Fn(&Cache, &CacheEvent) + Send + 'static
+    {
+        let mut matcher = self.lock();
+        matcher.on_cache_update(Box::new(listener));
+    }
+}
+
+impl Default for Biscuit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,19 +1,38 @@
+use std::collections::HashMap;
+#[cfg(feature = "pcap")]
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{Receiver, Sender};
 use lazy_static::lazy_static;
-use anyhow::{Result, anyhow};
-use dotenv_parser::parse_dotenv;
-use is_main_thread::is_main_thread;
 use crate::config::Config;
-use crate::matcher::{Cache, Matcher};
 
+pub use crate::error::BiscuitError;
+pub use crate::instance::Biscuit;
+pub use crate::cache::{Cache, CacheDiff, CacheEvent, FieldChange, FieldPresence, FieldValue, MessageField};
+pub use crate::matcher::{Direction, FeedOutcome, MatchResult, Packet, ProbeResult, SampleError, ScriptError, ScriptMatch, WarmupReport, WarmupSummary};
+pub use crate::metrics::Metrics;
+#[cfg(feature = "pcap")]
+pub use crate::pcap::ReplayStats;
+pub use protoshark::SerializedMessage as ProtoMessage;
+
+mod cache;
 pub mod config;
+mod env;
+pub mod error;
+mod instance;
 mod matcher;
 mod utils;
 mod message;
+mod metrics;
+#[cfg(feature = "pcap")]
+mod pcap;
+mod schema;
+mod source;
+mod wire;
+mod worker;
 
 lazy_static! {
-    static ref MATCHER: Arc<Mutex<Matcher>> = Arc::new(Mutex::new(Matcher::new()));
+    /// The instance used by the free functions in this crate.
+    static ref DEFAULT: Biscuit = Biscuit::new();
 }
 
 /// Initializes the library.
@@ -29,40 +48,17 @@ lazy_static! {
 /// biscuit::initialize(config)
 ///     .expect("invalid configuration specified");
 /// ```
-pub fn initialize(config: Config) -> Result<()> {
-    let mut matcher = MATCHER.lock().unwrap();
-
-    // Load all matcher scripts.
-    let path = config.script_path.clone();
-    let path = Path::new(&path);
-    if !path.exists() {
-        return Err(anyhow!("script folder does not exist"));
-    }
-
-    // Try loading the environment file.
-    let env_file = &config.environment_file;
-    let variables = {
-        let file = Path::new(env_file);
-        
-        if file.exists() {
-            let content = std::fs::read_to_string(file)?;
-            match parse_dotenv(&content) {
-                Ok(map) => Some(map),
-                Err(error) => {
-                    log::warn!("failed to parse environment file: {}", error);
-                    None
-                }
-            }
-        } else {
-            None
-        }
-    };
-
-    // Initialize the matcher.
-    matcher.config = Arc::new(config);
-    matcher.initialize(path, variables)?;
-
-    Ok(())
+pub fn initialize(config: Config) -> Result<(), BiscuitError> {
+    DEFAULT.initialize(config)
+}
+
+/// Initializes the library with scripts provided in memory, keyed by file name.
+///
+/// This allows scripts to be embedded in the binary, such as with `include_str!`.
+/// `Config::script_path` is not read, but modules loaded with `require` or `import`
+/// are still resolved against the working directory.
+pub fn initialize_from_sources(config: Config, sources: HashMap<String, String>) -> Result<(), BiscuitError> {
+    DEFAULT.initialize_from_sources(config, sources)
 }
 
 /// Processes the input data.
@@ -70,28 +66,271 @@ pub fn initialize(config: Config) -> Result<()> {
 /// # Notice
 /// 
 /// This should **only** be called on the main thread.
-pub fn input(id: u16, header: &[u8], data: &[u8]) -> Result<()> {
-    // Check if we are on the main thread.
-    let is_main = is_main_thread().unwrap_or_else(|| true);
-    if !is_main {
-        return Err(anyhow!("input can only be called on the main thread"));
-    }
-    
-    // Fetch the matcher.
-    let mut matcher = MATCHER.lock().unwrap();
-    
-    // Compare the data.
-    matcher.compare(id, header, data)?;
-    
-    Ok(())
+///
+/// If `Config::allow_any_thread` is set, this should instead **only** be
+/// called on the thread which called `initialize`.
+///
+/// If the direction of the packet is not known, use `Direction::Unknown`.
+///
+/// Returns the packet's name if it was newly identified by this packet.
+pub fn input(
+    id: u16,
+    direction: Direction,
+    header: &[u8],
+    data: &[u8]
+) -> Result<Option<String>, BiscuitError> {
+    DEFAULT.input(id, direction, header, data)
+}
+
+/// Processes data which has already been decoded.
+///
+/// This avoids decoding the packet again when the caller has already parsed it.
+/// Since the encoded bytes are not available, scripts cannot read raw field bytes.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn input_decoded(
+    id: u16,
+    direction: Direction,
+    header: ProtoMessage,
+    data: ProtoMessage
+) -> Result<Option<String>, BiscuitError> {
+    DEFAULT.input_decoded(id, direction, header, data)
+}
+
+/// Processes the input data, describing why it could not be decoded.
+///
+/// This is intended for debugging captures which do not decode.
+/// Unlike `input`, the packet is decoded even if no script would be run,
+/// and a header which cannot be decoded is reported even if `Config::strict_header` is not set.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn feed_raw(
+    id: u16,
+    direction: Direction,
+    header: &[u8],
+    data: &[u8]
+) -> Result<FeedOutcome, BiscuitError> {
+    DEFAULT.feed_raw(id, direction, header, data)
+}
+
+/// Processes multiple packets in order.
+///
+/// This only locks the matcher once, which is faster when replaying a capture.
+///
+/// The result of each packet is returned in the same order as the input.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn input_batch(
+    packets: &[Packet]
+) -> Result<Vec<Result<Option<String>, BiscuitError>>, BiscuitError> {
+    DEFAULT.input_batch(packets)
+}
+
+/// Starts a thread which owns the matcher, and feeds it packets over a channel.
+///
+/// The scripts are loaded on the new thread, which is the only thread that may then provide packets.
+/// `Config::allow_any_thread` is enabled to allow this.
+///
+/// Each packet sent to the returned sender produces one result on the returned receiver,
+/// in the same order. Dropping the sender unloads the scripts and stops the thread.
+///
+/// This allows packets to be provided from async code without blocking on the matcher.
+///
+/// Returns an error if the scripts fail to load.
+pub fn spawn_worker(config: Config) -> Result<(Sender<Packet>, Receiver<MatchResult>), BiscuitError> {
+    DEFAULT.spawn_worker(config)
+}
+
+/// Replays a pcap or pcapng capture file through the matcher.
+///
+/// The extractor is given the captured bytes of each packet, including any link-layer headers,
+/// and returns the packet to provide to the matcher. Packets for which it returns `None` are skipped.
+///
+/// This requires the `pcap` feature.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+#[cfg(feature = "pcap")]
+pub fn replay_pcap<F>(path: impl AsRef<Path>, extractor: F) -> Result<ReplayStats, BiscuitError>
+where
+    F: FnMut(&[u8]) -> Option<Packet>
+{
+    DEFAULT.replay_pcap(path, extractor)
+}
+
+/// Runs every script against sample packets, without keeping any identifications.
+///
+/// Each sample is a packet ID, header, and data.
+///
+/// This can be used to check that scripts run without errors before processing live traffic.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn warmup(samples: &[(u16, Vec<u8>, Vec<u8>)]) -> Result<WarmupSummary, BiscuitError> {
+    DEFAULT.warmup(samples)
+}
+
+/// Forces every script to compare a packet, and reports the outcome of each.
+///
+/// Unlike `input`, scripts are run even if the packet is known to belong to another script.
+/// Any identifications made are discarded.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn probe(id: u16, header: &[u8], data: &[u8]) -> Result<Vec<ProbeResult>, BiscuitError> {
+    DEFAULT.probe(id, header, data)
+}
+
+/// Renders a one-line summary of a packet, using the `format` function of its script.
+///
+/// The packet's name must already be known, and the script which identifies
+/// that name must export `format(id, data)`. Otherwise, `None` is returned.
+///
+/// # Notice
+///
+/// This has the same thread requirements as `input`.
+pub fn format_packet(id: u16, header: &[u8], data: &[u8]) -> Result<Option<String>, BiscuitError> {
+    DEFAULT.format_packet(id, header, data)
 }
 
 /// Fetches the cache.
 ///
 /// This returns a clone.
 pub fn cache() -> Cache {
-    let matcher = MATCHER.lock().unwrap();
-    let cache = matcher.cache.lock().unwrap();
+    DEFAULT.cache()
+}
+
+/// Renames a packet in the cache, keeping its ID and fields.
+///
+/// This is used to correct a packet name which was guessed wrong.
+/// The name is left unchanged if the new name is already in use.
+///
+/// Returns whether the packet was renamed.
+pub fn rename_packet(old_name: &str, new_name: &str) -> bool {
+    DEFAULT.rename_packet(old_name, new_name)
+}
+
+/// Reverts the most recent change made to the cache.
+///
+/// If the change added a new packet, the packet and its fields are removed entirely.
+/// If it added a field to a known packet, only that field is removed.
+/// Changes can be reverted repeatedly, newest first.
+/// Only the most recent `Config::history_limit` changes can be reverted.
+///
+/// Returns whether there was a change to revert.
+pub fn undo() -> bool {
+    DEFAULT.undo()
+}
+
+/// Fetches the most recent changes made to the cache, in the order they were made.
+///
+/// Reverted changes are kept, followed by a `CacheEvent::Undone` event.
+/// At most `Config::history_limit` events are kept.
+pub fn history() -> Vec<CacheEvent> {
+    DEFAULT.history()
+}
+
+/// Adds the messages of a compiled `FileDescriptorSet` to the cache.
+///
+/// This is useful when part of the schema is already known.
+/// The names, IDs, and types of every field are added with full confidence.
+/// Nested messages are named with their parents' names, such as `Outer.Inner`.
+/// The values of enum fields are named as if by `registerEnum`.
+///
+/// This requires the `descriptor` feature.
+///
+/// # Notice
+///
+/// Enum value names are cleared by `initialize`, so this should be called afterwards.
+#[cfg(feature = "descriptor")]
+pub fn load_descriptor(descriptor: &[u8]) -> Result<(), BiscuitError> {
+    DEFAULT.load_descriptor(descriptor)
+}
+
+/// Fetches the cache updated by scripts while `Config::dry_run` is set.
+///
+/// Use `Cache::diff` against `cache` to see what the scripts would have identified.
+/// If `Config::dry_run` is not set, the returned cache is empty.
+///
+/// This returns a clone.
+pub fn dry_run_results() -> Cache {
+    DEFAULT.dry_run_results()
+}
+
+/// Fetches the cache as pretty-printed JSON.
+pub fn cache_json() -> Result<String, BiscuitError> {
+    DEFAULT.cache_json()
+}
+
+/// Fetches the most recent errors raised by each script.
+///
+/// This returns a clone.
+pub fn recent_errors() -> Vec<ScriptError> {
+    DEFAULT.recent_errors()
+}
+
+/// Fetches the matcher's metrics.
+///
+/// This returns a clone.
+pub fn metrics() -> Metrics {
+    DEFAULT.metrics()
+}
+
+/// Fetches the number of times each unknown packet ID was not matched by any script.
+///
+/// IDs with the highest counts are the best candidates for new scripts.
+///
+/// This returns a clone.
+pub fn unmatched_ids() -> HashMap<u16, u64> {
+    DEFAULT.unmatched_ids()
+}
+
+/// Renders a human-readable summary of the loaded scripts, cache, and metrics.
+///
+/// This is intended for status reports and logs.
+pub fn describe() -> String {
+    DEFAULT.describe()
+}
+
+/// Resets the matcher's metrics to zero.
+///
+/// The cache and loaded scripts are left unchanged.
+pub fn reset_metrics() {
+    DEFAULT.reset_metrics()
+}
+
+/// Unloads all scripts, running their `teardown` functions.
+///
+/// The cache is left unchanged, and scripts can be loaded again with `initialize`.
+///
+/// # Notice
+///
+/// This must be called on the same thread as `initialize`,
+/// since the script contexts are owned by that thread.
+pub fn shutdown() -> Result<(), BiscuitError> {
+    DEFAULT.shutdown()
+}
 
-    cache.clone()
+/// Registers a function which is called whenever the cache is updated.
+///
+/// The function is called once for every change, after each packet is compared.
+///
+/// # Notice
+///
+/// The function is called while the library is locked,
+/// so it must not call any other function in this library.
+pub fn on_cache_update<F>(listener: F)
+where
+    F: Fn(&Cache, &CacheEvent) + Send + 'static
+{
+    DEFAULT.on_cache_update(listener)
 }
\ No newline at end of file
@@ -1,106 +1,397 @@
-use std::collections::{BTreeMap, HashMap};
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{Debug, Formatter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::ThreadId;
 use anyhow::{anyhow, Result};
 use boa_engine::{js_string, Context, Finalize, JsArgs, JsData, JsNativeError, JsObject, JsResult, JsString, JsValue, NativeFunction, Source, Trace};
-use boa_engine::module::SimpleModuleLoader;
-use boa_engine::object::builtins::JsMap;
-use boa_engine::property::Attribute;
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::module::{Module, SimpleModuleLoader};
+use boa_engine::object::builtins::{JsArray, JsArrayBuffer, JsMap};
+use boa_engine::object::{FunctionObjectBuilder, IntegrityLevel, ObjectInitializer};
+use boa_engine::property::{Attribute, PropertyKey};
 use boa_engine::realm::Realm;
 use boa_engine::value::{TryFromJs, TryIntoJs};
 use boa_runtime::Console;
-use log::warn;
+use log::{debug, error, info, warn, LevelFilter};
 use serde::{Deserialize, Serialize};
-use protoshark::{SerializedMessage as ProtoMessage};
+use crate::cache::{Cache, CacheEvent, MessageField};
 use crate::config::Config;
-use crate::message::SerializedMessage;
-use crate::{js_catch, js_get, from_realm, js_error, js_convert, utils};
-
-/// Represents a JavaScript object containing field data.
-#[derive(Deserialize, Serialize, Clone, Debug, Default, Trace, Finalize, TryFromJs)]
-pub struct MessageField {
-    /// The name of the field.
-    /// 
-    /// # Repeated Names
-    /// 
-    /// If this field name is repeated, the other fields will be categorized under a `oneof`.
-    pub field_name: String,
-    
-    /// The type of the field.
-    pub field_type: String,
-    
-    /// The ID of the field.
-    /// 
-    /// This must be unique.
-    pub field_id: u16
+use crate::error::BiscuitError;
+use crate::message::{DecodedMessage, SerializedMessage};
+use crate::metrics::Metrics;
+use crate::ProtoMessage;
+use crate::source::ScriptSources;
+use crate::utils::{JsEnv, JsLogLevel, JsRandom};
+use crate::worker::Worker;
+use crate::{js_catch, js_get, from_realm, js_error, js_convert, schema, utils, wire};
+
+/// The direction a packet was sent in.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The packet was received by the client.
+    Inbound,
+
+    /// The packet was sent by the client.
+    Outbound,
+
+    /// The direction of the packet could not be determined.
+    #[default]
+    Unknown
+}
+
+impl Direction {
+    /// Returns the name of the direction, as provided to scripts.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Inbound => "inbound",
+            Direction::Outbound => "outbound",
+            Direction::Unknown => "unknown"
+        }
+    }
+}
+
+/// A single captured packet.
+#[derive(Clone, Debug, Default)]
+pub struct Packet {
+    /// The ID of the packet.
+    pub id: u16,
+
+    /// The direction the packet was sent in.
+    pub direction: Direction,
+
+    /// The encoded packet header.
+    pub header: Vec<u8>,
+
+    /// The encoded packet data.
+    pub data: Vec<u8>
+}
+
+/// The outcome of a packet sent to a worker started with `spawn_worker`.
+#[derive(Debug)]
+pub struct MatchResult {
+    /// The ID of the packet.
+    pub id: u16,
+
+    /// The packet's name if it was newly identified, or the error raised while processing it.
+    pub result: Result<Option<String>, BiscuitError>
+}
+
+/// A script which matched a packet provided with `feed_raw`.
+#[derive(Clone, Debug)]
+pub struct ScriptMatch {
+    /// The file name of the script.
+    pub script: String,
+
+    /// The packet's name if it was newly identified by this packet.
+    pub name: Option<String>
 }
 
-/// Represents the deobfuscated packet cache.
-#[derive(Deserialize, Serialize, Clone, Debug, Default)]
-pub struct Cache {
-    /// This is an array of known packet names.
+/// The outcome of a packet provided with `feed_raw`.
+#[derive(Debug)]
+pub enum FeedOutcome {
+    /// The header could not be decoded, for the given reason.
     ///
-    /// This is not definitive, and is used only for quick reference.
-    known_names: Vec<String>,
+    /// This is reported even if `Config::strict_header` is not set,
+    /// in which case `Matcher::compare` would use an empty header instead.
+    HeaderDecodeFailed(String),
+
+    /// The packet data could not be decoded, for the given reason.
+    BodyDecodeFailed(String),
+
+    /// The packet was decoded, but no script reported a match.
+    NoMatch,
 
-    /// This is an array of known packet IDs.
+    /// At least one script reported a match.
     ///
-    /// This is not definitive, and is used only for quick reference.
-    known_ids: Vec<u16>,
+    /// This contains an entry for each script which reported a match.
+    Matched(Vec<ScriptMatch>)
+}
 
-    /// This maps packet IDs to their guessed name.
-    id_map: HashMap<u16, String>,
-    
-    /// This maps guessed names to their packet IDs.
-    name_map: HashMap<String, u16>,
-    
-    /// All cached messages.
-    messages: HashMap<String, Vec<MessageField>>
+/// An error raised by a script while comparing a packet.
+#[derive(Clone, Debug)]
+pub struct ScriptError {
+    /// The file name of the script.
+    pub script: String,
+
+    /// The ID of the packet being compared.
+    pub packet_id: u16,
+
+    /// The error message.
+    pub message: String
+}
+
+/// The outcome of forcing a script to compare a packet.
+#[derive(Clone, Debug)]
+pub struct ProbeResult {
+    /// The file name of the script.
+    pub script: String,
+
+    /// Whether the script reported a match by returning `true`.
+    pub matched: bool,
+
+    /// The error raised by the script, if it failed.
+    pub error: Option<String>
+}
+
+/// The outcome of running a script against sample packets.
+#[derive(Clone, Debug)]
+pub struct WarmupReport {
+    /// The file name of the script.
+    pub script: String,
+
+    /// The number of samples the script was run against.
+    pub runs: u64,
+
+    /// The errors raised by the script.
+    pub errors: Vec<ScriptError>
 }
 
-impl Cache {
-    /// Simple check to see if the cache knows the given ID.
-    pub fn id_known(&self, id: u16) -> bool {
-        self.id_map.contains_key(&id)
+impl WarmupReport {
+    /// Checks if the script ran without any errors.
+    pub fn succeeded(&self) -> bool {
+        self.errors.is_empty()
     }
-    
-    /// Simple check to see if the cache knows the given name.
-    pub fn name_known(&self, name: &str) -> bool {
-        self.known_names.contains(&name.to_string())
+}
+
+/// A sample packet which could not be decoded during a warmup.
+#[derive(Clone, Debug)]
+pub struct SampleError {
+    /// The position of the sample in the list of samples.
+    pub index: usize,
+
+    /// The ID of the packet.
+    pub packet_id: u16,
+
+    /// The error message.
+    pub message: String
+}
+
+/// The outcome of running every script against sample packets.
+#[derive(Clone, Debug)]
+pub struct WarmupSummary {
+    /// A report for every loaded script.
+    pub reports: Vec<WarmupReport>,
+
+    /// The samples which could not be decoded, and were skipped.
+    pub decode_errors: Vec<SampleError>
+}
+
+impl WarmupSummary {
+    /// Checks if every sample was decoded and every script ran without any errors.
+    pub fn succeeded(&self) -> bool {
+        self.decode_errors.is_empty() && self.reports.iter().all(WarmupReport::succeeded)
     }
-    
-    /// Updates the cache with the guessed name, ID, and field data.
-    pub fn update(
-        &mut self,
-        message_name: String,
-        packet_id: u16,
-        field: MessageField
-    ) {
-        // Add the message to the cache if it doesn't exist.
-        if !self.id_map.contains_key(&packet_id) {
-            self.known_names.push(message_name.clone());
-            self.known_ids.push(packet_id);
-            
-            self.id_map.insert(packet_id, message_name.clone());
-            self.name_map.insert(message_name.clone(), packet_id);
+}
+
+/// Represents a JavaScript object containing packet data.
+#[derive(Trace, Finalize, JsData)]
+pub(crate) struct JsCache(#[unsafe_ignore_trace] pub GlobalCache);
+
+/// Represents the modules which have been required in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+struct JsModules {
+    /// The script's own directory, which the global `require` resolves paths against.
+    #[unsafe_ignore_trace]
+    directory: PathBuf,
+
+    /// The exports of each evaluated file, keyed by absolute path.
+    exports: HashMap<String, JsValue>,
+
+    /// The absolute path of the directory which required files must be inside of.
+    #[unsafe_ignore_trace]
+    root: PathBuf
+}
+
+impl JsModules {
+    /// Creates the module state for a script in the given directory.
+    ///
+    /// The directory is also used as the root which required files must be inside of.
+    fn new(directory: PathBuf) -> Self {
+        JsModules {
+            root: absolute(&directory),
+            directory,
+            exports: HashMap::new()
         }
-        
-        // Add the field to the message.
-        let fields = self.messages.entry(message_name).or_default();
-        fields.push(field);
     }
 }
 
-/// Represents a JavaScript object containing packet data.
+/// Represents the number of identifications made by a script in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+struct JsStats(#[unsafe_ignore_trace] Rc<Cell<u64>>);
+
+/// Represents the fields most recently identified by a script in a JavaScript realm.
+///
+/// Repeating an identification does not change the cache, so it is skipped without locking the cache.
+/// Fields are keyed by packet name, packet ID, and field ID, and are only trusted
+/// while the cache's generation is the one they were recorded at.
+#[derive(Trace, Finalize, JsData)]
+struct JsIdentified {
+    /// The cache's generation counter.
+    #[unsafe_ignore_trace]
+    generation: Arc<AtomicU64>,
+
+    /// The generation of the cache when the fields were recorded.
+    #[unsafe_ignore_trace]
+    recorded: Cell<u64>,
+
+    /// The most recent identification of each field.
+    #[unsafe_ignore_trace]
+    fields: RefCell<HashMap<(String, u16, u16), MessageField>>
+}
+
+impl JsIdentified {
+    /// Creates an empty set of identifications for the cache with the given generation counter.
+    fn new(generation: Arc<AtomicU64>) -> Self {
+        JsIdentified {
+            recorded: Cell::new(generation.load(Ordering::Acquire)),
+            generation,
+            fields: RefCell::default()
+        }
+    }
+
+    /// Checks if the cache has not changed since the fields were recorded.
+    fn is_current(&self) -> bool {
+        self.generation.load(Ordering::Acquire) == self.recorded.get()
+    }
+}
+
+/// Represents the matcher's configuration in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+pub(crate) struct JsConfig(#[unsafe_ignore_trace] pub Arc<Config>);
+
+/// Represents the state shared between all scripts in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+struct JsShared(#[unsafe_ignore_trace] SharedState);
+
+/// Represents the enum value names registered by scripts in a JavaScript realm.
 #[derive(Trace, Finalize, JsData)]
-struct JsCache(#[unsafe_ignore_trace] GlobalCache);
+pub(crate) struct JsEnums(#[unsafe_ignore_trace] pub EnumTable);
 
 /// This type is an alias for a cache shared between comparers.
-type GlobalCache = Arc<Mutex<Cache>>;
+pub(crate) type GlobalCache = Arc<Mutex<Cache>>;
+
+/// This type is an alias for the key-value map shared between comparers.
+///
+/// Values are stored as JSON, since JavaScript values cannot be moved between contexts.
+pub(crate) type SharedState = Arc<Mutex<HashMap<String, serde_json::Value>>>;
+
+/// The exports of files loaded with `requireOnce`, shared between comparers.
+///
+/// Exports are stored as JSON, since JavaScript values cannot be moved between contexts.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedModules(Arc<(Mutex<HashMap<String, SharedModule>>, Condvar)>);
+
+/// The state of a file loaded with `requireOnce`.
+#[derive(Debug)]
+enum SharedModule {
+    /// The file is being evaluated on the given thread.
+    Loading(ThreadId),
+
+    /// The file was evaluated, and these are its exports.
+    Loaded(serde_json::Value),
+
+    /// The file failed to evaluate, or its exports could not be shared.
+    Failed(String)
+}
+
+impl SharedModules {
+    /// Claims a file for evaluation, or fetches its exports if it was already evaluated.
+    ///
+    /// If the file is being evaluated on another thread, this waits for it to finish,
+    /// unless this thread is evaluating another file, since the two could be waiting on each other.
+    ///
+    /// Returns `None` if the caller should evaluate the file, and then call `finish`.
+    fn begin(&self, key: &str) -> Result<Option<serde_json::Value>, String> {
+        let (modules, loaded) = &*self.0;
+        let current = std::thread::current().id();
+
+        let mut modules = modules.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let loading = modules.values()
+                .any(|module| matches!(module, SharedModule::Loading(thread) if *thread == current));
+
+            match modules.get(key) {
+                Some(SharedModule::Loaded(exports)) => return Ok(Some(exports.clone())),
+                Some(SharedModule::Failed(error)) => return Err(error.clone()),
+                Some(SharedModule::Loading(thread)) if *thread == current => {
+                    return Err(format!("circular requireOnce of '{}'", key));
+                },
+                Some(SharedModule::Loading(_)) if loading => {
+                    return Err(format!("'{}' is being evaluated by another script", key));
+                },
+                Some(SharedModule::Loading(_)) => {
+                    modules = loaded.wait(modules).unwrap();
+                },
+                None => {
+                    modules.insert(key.to_string(), SharedModule::Loading(current));
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Records the exports of a claimed file, or the reason it could not be shared.
+    ///
+    /// Failures are remembered, so the file is not evaluated again.
+    fn finish(&self, key: &str, exports: Result<serde_json::Value, String>) {
+        let (modules, loaded) = &*self.0;
+
+        let module = match exports {
+            Ok(exports) => SharedModule::Loaded(exports),
+            Err(error) => SharedModule::Failed(error)
+        };
+        modules.lock().unwrap_or_else(PoisonError::into_inner).insert(key.to_string(), module);
+
+        loaded.notify_all();
+    }
+
+    /// Forgets the exports of every file.
+    fn clear(&self) {
+        self.0.0.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+/// Represents the files loaded with `requireOnce` in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+struct JsSharedModules(#[unsafe_ignore_trace] SharedModules);
+
+/// This type is an alias for the enum value names shared between comparers.
+///
+/// Names are keyed by message name, then field ID, then value.
+pub(crate) type EnumTable = Arc<Mutex<HashMap<String, HashMap<i32, HashMap<i64, String>>>>>;
 
 /// This type is an alias for the environment variables map.
-type Env = BTreeMap<String, String>;
+pub(crate) type Env = BTreeMap<String, String>;
+
+/// Host-side state which is provided to every comparer when it is loaded.
+#[derive(Clone, Debug)]
+pub(crate) struct HostState {
+    pub config: Arc<Config>,
+    pub cache: GlobalCache,
+    pub shared: SharedState,
+    pub enums: EnumTable,
+    pub modules: SharedModules,
+    pub env_vars: Arc<Env>,
+    pub sources: ScriptSources
+}
+
+/// A function which is called when the cache is updated.
+pub type CacheListener = Box<dyn Fn(&Cache, &CacheEvent) + Send>;
+
+/// A collection of cache listeners.
+#[derive(Default)]
+struct Listeners(Vec<CacheListener>);
+
+impl Debug for Listeners {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Listeners({})", self.0.len())
+    }
+}
 
 /// A matcher is a struct containing a group of comparers.
 ///
@@ -112,7 +403,63 @@ pub struct Matcher {
     pub config: Arc<Config>,
     pub cache: GlobalCache,
 
-    comparers: Vec<Comparer>
+    /// The key-value map shared between all scripts.
+    shared: SharedState,
+
+    /// The enum value names registered by scripts.
+    enums: EnumTable,
+
+    /// The exports of files loaded with `requireOnce`.
+    modules: SharedModules,
+
+    /// The cache which scripts update while in dry-run mode.
+    ///
+    /// This is a copy of the real cache with `dry_run_changes` applied, and is never merged into it.
+    scratch: GlobalCache,
+
+    /// The changes scripts made to the scratch cache.
+    ///
+    /// These are applied again whenever the scratch cache is copied from the real cache.
+    dry_run_changes: Vec<CacheEvent>,
+
+    comparers: Vec<Comparer>,
+
+    /// Worker threads which own their own comparers.
+    ///
+    /// When this is empty, all comparers are run on the calling thread.
+    workers: Vec<Worker>,
+
+    /// The packet names of all loaded comparers.
+    names: Vec<String>,
+
+    /// The file names of all loaded comparers.
+    scripts: Vec<String>,
+
+    /// Whether any loaded comparer sets `ALWAYS_RUN`.
+    always_run: bool,
+
+    /// The file name of the script whose `decrypt` function is run on every packet.
+    decrypter: Option<String>,
+
+    /// The most recent errors raised by each script.
+    errors: HashMap<String, VecDeque<ScriptError>>,
+
+    /// Counters describing the work done by the matcher.
+    metrics: Metrics,
+
+    /// The number of times each unknown packet ID was not matched by any script.
+    unmatched_ids: HashMap<u16, u64>,
+
+    /// Functions which are called when the cache is updated.
+    listeners: Listeners,
+
+    /// The changes which were sent to the listeners, in order.
+    ///
+    /// Only the most recent `Config::history_limit` events are kept.
+    history: VecDeque<CacheEvent>,
+
+    /// The thread which loaded the comparers.
+    thread: Option<ThreadId>
 }
 
 impl Matcher {
@@ -121,22 +468,78 @@ impl Matcher {
         Matcher {
             config: Arc::new(Config::default()),
             cache: Arc::new(Mutex::new(Cache::default())),
-            comparers: vec![]
+            shared: SharedState::default(),
+            enums: EnumTable::default(),
+            modules: SharedModules::default(),
+            scratch: GlobalCache::default(),
+            dry_run_changes: vec![],
+            comparers: vec![],
+            workers: vec![],
+            names: vec![],
+            scripts: vec![],
+            always_run: false,
+            decrypter: None,
+            errors: HashMap::new(),
+            metrics: Metrics::default(),
+            unmatched_ids: HashMap::new(),
+            listeners: Listeners::default(),
+            history: VecDeque::new(),
+            thread: None
+        }
+    }
+
+    /// Checks if the current thread is the thread which initialized the matcher.
+    pub fn on_initializing_thread(&self) -> bool {
+        self.thread == Some(std::thread::current().id())
+    }
+
+    /// Unloads all comparers, running their teardown functions.
+    ///
+    /// This must be called on the thread which initialized the matcher,
+    /// since the script contexts are owned by that thread.
+    /// The cache is left unchanged.
+    pub fn shutdown(&mut self) -> Result<(), BiscuitError> {
+        if self.thread.is_some() && !self.on_initializing_thread() {
+            return Err(BiscuitError::NotInitializingThread);
+        }
+
+        self.unload();
+        self.names.clear();
+        self.scripts.clear();
+        self.always_run = false;
+        self.decrypter = None;
+        self.thread = None;
+
+        Ok(())
+    }
+
+    /// Unloads all comparers, running their teardown functions.
+    ///
+    /// Workers tear down their comparers on their own threads.
+    /// Comparers loaded on another thread cannot be used or dropped here,
+    /// so they are leaked without running their teardown functions.
+    fn unload(&mut self) {
+        let comparers = std::mem::take(&mut self.comparers);
+        if self.thread.is_none() || self.on_initializing_thread() {
+            // Dropping a comparer runs its teardown function.
+            drop(comparers);
+        } else if !comparers.is_empty() {
+            warn!(
+                "Unloaded {} scripts off of the thread which loaded them, so their teardown functions were not run",
+                comparers.len()
+            );
+            std::mem::forget(comparers);
         }
+
+        self.workers.clear();
     }
 
     /// Loads all scripts from the specified path.
     pub fn initialize(&mut self, path: &Path, env_vars: Option<Env>) -> Result<()> {
-        // Check if environment variables exist.
-        let env_vars = match env_vars {
-            Some(value) => value,
-            None => Env::new()
-        };
-
-        // Create the module loader.
-        let loader = Rc::new(js_catch!(SimpleModuleLoader::new(&path)));
+        let host = self.prepare(env_vars)?;
 
         // Enumerate the directory for JavaScript files.
+        let mut scripts = vec![];
         for entry in path.read_dir()? {
             // Check if the entry is an error.
             let entry = match entry {
@@ -158,178 +561,1563 @@ impl Matcher {
                 None => continue
             };
 
-            match Comparer::from(
-                &loader, &entry, self.cache.clone(), &env_vars
-            ) {
-                Ok(Some(script)) => self.comparers.push(script),
-                Err(error) => {
-                    warn!(
-                        "Invalid script while parsing '{}' (maybe syntax error?): {}",
-                        entry.to_string_lossy(),
-                        error
-                    );
-                },
-                _ => continue
-            };
+            scripts.push(entry);
         }
 
-        Ok(())
+        self.load(path, scripts, &host)
     }
 
-    /// Provides the given data to the matcher.
+    /// Loads scripts from their sources, keyed by file name.
     ///
-    /// The data is first decoded, then checked against all comparers.
-    pub fn compare(&mut self, id: u16, header: &[u8], data: &[u8]) -> Result<()> {
-        // Decode the data.
-        let data = match protoshark::decode(data) {
-            Ok(decoded) => decoded,
-            Err(error) => {
-                return Err(anyhow!("failed to decode packet: {:#?}", error));
-            }
-        };
+    /// This allows scripts to be embedded in the binary, such as with `include_str!`.
+    /// Modules loaded with `require` or `import` are still read from the working directory.
+    /// The sources are only kept until the next time scripts are loaded.
+    pub fn initialize_from_sources(
+        &mut self,
+        sources: HashMap<String, String>,
+        env_vars: Option<Env>
+    ) -> Result<()> {
+        let mut host = self.prepare(env_vars)?;
 
-        let header = match protoshark::decode(header) {
-            Ok(decoded) => decoded,
-            Err(error) => {
-                return Err(anyhow!("failed to decode header: {:#?}", error));
-            }
-        };
+        // Provide the sources in place of the script files.
+        let sources: HashMap<PathBuf, Arc<[u8]>> = sources.into_iter()
+            .map(|(name, source)| (PathBuf::from(name), source.into_bytes().into()))
+            .collect();
+        let mut scripts: Vec<PathBuf> = sources.keys().cloned().collect();
+        host.sources = ScriptSources::from(sources);
 
-        // Send the data to each comparer.
-        for comparer in &mut self.comparers {
-            // Check the cache to see if the packet is known.
-            let cache = self.cache.lock().unwrap();
-            if let Some(known_id) = cache.name_map.get(&comparer.name) {
-                if known_id != &id {
-                    continue;
-                }
-            }
-            
-            // Unlock the cache.
-            drop(cache);
-            
-            if let Err(error) = comparer.compare(id, &header, &data) {
-                warn!("Failed to compare packet: {:#?}", error);
-            }
+        // Load the scripts in a consistent order.
+        scripts.sort();
+
+        self.load(Path::new("."), scripts, &host)
+    }
+
+    /// Checks a configuration before it is used to initialize the matcher.
+    ///
+    /// This is called before any state is changed, so an invalid configuration
+    /// leaves the previously loaded scripts running.
+    pub fn validate_config(config: &Config) -> Result<()> {
+        if config.script_log_level.parse::<LevelFilter>().is_err() {
+            return Err(anyhow!("invalid script log level: {}", config.script_log_level));
         }
 
         Ok(())
     }
-}
 
-#[derive(Debug)]
-pub struct Comparer {
-    context: Context,
-    pub name: String
-}
+    /// Resets the matcher's state before loading scripts.
+    ///
+    /// Returns the state shared with the scripts.
+    fn prepare(&mut self, env_vars: Option<Env>) -> Result<HostState> {
+        // Unload any previously loaded comparers, on the thread which owns them.
+        self.unload();
 
-/// This unsafe implementation is used to allow any comparers to be sent between threads.
-///
-/// A JavaScript `Context` is not thread-safe, so ensure that it is always being called on the same thread.
-unsafe impl Send for Comparer {}
+        // Record the thread which owns the script contexts.
+        self.thread = Some(std::thread::current().id());
 
-impl Comparer {
-    /// The name of the environment variables global property.
-    const ENV_VARS_NAME: JsString = js_string!("env");
+        // Add the fields of the known schema to the cache.
+        if let Some(schema) = &self.config.proto_schema {
+            self.load_schema(Path::new(schema))?;
+        }
 
-    /// Creates a script instance from the contents of script.
-    pub fn from(
-        loader: &Rc<SimpleModuleLoader>,
-        script: &Path,
-        cache: GlobalCache,
-        env_vars: &Env
-    ) -> Result<Option<Self>> {
-        // Parse the script.
-        let source = Source::from_filepath(script)?;
+        // Bound the changes which can be reverted.
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner).set_history_limit(self.config.history_limit);
 
-        // Create a script context.
-        let mut context = js_catch!(Context::builder()
-            .module_loader(loader.clone())
-            .build());
+        // In dry-run mode, scripts start from a copy of the real cache.
+        self.dry_run_changes.clear();
+        if self.config.dry_run {
+            // Pending changes to the real cache are not the scripts' changes.
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            cache.take_events();
+            self.scratch.lock().unwrap_or_else(PoisonError::into_inner).replace(cache);
+        } else {
+            self.scratch.lock().unwrap_or_else(PoisonError::into_inner).replace(Cache::default());
+        }
 
-        // Add the cache to the realm.
-        let realm = context.realm().clone();
-        realm
-            .host_defined_mut()
-            .insert(JsCache(cache.clone()));
-        
-        // Add the script's directory to the realm.
-        if let Some(directory) = script.parent() {
-            realm
-                .host_defined_mut()
-                .insert(directory.to_string_lossy().to_string());
+        // Scripts register their enums again when they are loaded.
+        self.enums.lock().unwrap_or_else(PoisonError::into_inner).clear();
+
+        // Files loaded with `requireOnce` are evaluated again, in case they changed.
+        self.modules.clear();
+
+        // Check if environment variables exist.
+        let env_vars = env_vars.unwrap_or_default();
+
+        let host = HostState {
+            config: self.config.clone(),
+            cache: self.working_cache().clone(),
+            shared: self.shared.clone(),
+            enums: self.enums.clone(),
+            modules: self.modules.clone(),
+            env_vars: Arc::new(env_vars),
+            sources: ScriptSources::default()
         };
 
-        // Add the environment variables to the context.
-        let map = JsMap::new(&mut context);
-        for (key, value) in env_vars {
-            let key = js_string!(key.clone());
-            let value = js_string!(value.clone());
-            js_catch!(map.set(key, value, &mut context));
+        Ok(host)
+    }
+
+    /// Creates comparers for the given scripts.
+    ///
+    /// Modules imported by the scripts are resolved relative to the path.
+    fn load(&mut self, path: &Path, scripts: Vec<PathBuf>, host: &HostState) -> Result<()> {
+        // If no workers are used, load the comparers on this thread.
+        let worker_count = self.config.worker_threads.min(scripts.len());
+        if worker_count == 0 {
+            self.comparers = load_comparers(path, &scripts, host)?;
+        } else {
+            self.spawn_workers(worker_count, path, scripts, host)?;
         }
 
-        // Update the runtime.
-        context
-            .register_global_property(Self::ENV_VARS_NAME, map, Attribute::all())
-            .expect("global property 'console' already exists");
+        // Collect the names of every loaded comparer.
+        self.names = self.dispatch(|comparers| {
+            comparers
+                .iter()
+                .flat_map(|comparer| comparer.names.clone())
+                .collect::<Vec<_>>()
+        }).concat();
 
-        declare_runtime(realm, &mut context)?;
+        // Collect the file names of every loaded comparer.
+        self.scripts = self.dispatch(|comparers| {
+            comparers
+                .iter()
+                .map(|comparer| comparer.script.clone())
+                .collect::<Vec<_>>()
+        }).concat();
+        self.scripts.sort();
 
-        // Load the script into the context.
-        if let Err(error) = context.eval(source) {
-            return Err(anyhow!("failed to evaluate script: {:#?}", error));
-        };
+        // Check if any comparer must always be run.
+        self.always_run = self.dispatch(|comparers| {
+            comparers.iter().any(|comparer| comparer.always_run)
+        }).contains(&true);
 
-        // If the function does not contain a 'compare' function, return `None`.
-        if js_get!(context, "compare"; as_callable).is_err() {
-            return Ok(None);
+        // Find the scripts which decrypt packets.
+        // Only the first, by file name, is used.
+        // Scripts must opt in with `DECRYPTS`, so an unrelated global `decrypt` function is never run.
+        let mut decrypters = self.dispatch(|comparers| {
+            comparers
+                .iter()
+                .filter(|comparer| comparer.decrypts)
+                .map(|comparer| comparer.script.clone())
+                .collect::<Vec<_>>()
+        }).concat();
+        decrypters.sort();
+
+        for script in decrypters.iter().skip(1) {
+            warn!(
+                script = script.as_str();
+                "Script '{}' sets DECRYPTS, but '{}' is used instead",
+                script, decrypters[0]
+            );
         }
-        
-        // Get the script's packet name.
-        let Ok(name) = js_get!(context, "PACKET_NAME"; as_string) else {
-            return Err(anyhow!("failed to get packet name"));
-        };
-        let name = name.to_std_string_escaped();
+        self.decrypter = decrypters.into_iter().next();
 
-        // Run the initialize function if it exists.
-        if let Ok(initialize) = js_get!(context, "init"; as_callable) {
-            js_catch!(initialize.call(&JsValue::undefined(), &[], &mut context));
+        // Run the post-load script, if configured.
+        if let Some(script) = &self.config.post_load_script {
+            Comparer::run_post_load(path, Path::new(script), &self.names, host)?;
         }
 
-        Ok(Some(Comparer { context, name }))
+        // Scripts may have updated the cache while initializing.
+        self.notify();
+
+        Ok(())
     }
 
-    /// Provides the given data to the comparer.
-    ///
-    /// This will run the comparer's logic and return the result.
-    pub fn compare(&mut self, id: u16, header: &ProtoMessage, data: &ProtoMessage) -> Result<()> {
-        // Convert parameters into JavaScript objects.
-        let id = js_catch!(id.try_into_js(&mut self.context));
-        let header = SerializedMessage::from_to_js(&mut self.context, header)?;
-        let data = SerializedMessage::from_to_js(&mut self.context, data)?;
+    /// Adds the messages of a `.proto` file to the cache.
+    fn load_schema(&self, path: &Path) -> Result<()> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| anyhow!("failed to read schema '{}': {}", path.to_string_lossy(), error))?;
+        let messages = schema::parse_proto(&source)?;
 
-        // Find the compare function.
-        // If it doesn't exist, we can't compare the data.
-        let compare = match js_get!(self.context, "compare"; as_callable) {
-            Ok(compare) => compare,
-            Err(error) => return Err(error)
-        };
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, fields) in messages {
+            info!("Loaded schema message '{}' with {} fields", name, fields.len());
 
-        // Run the compare function.
-        if let Err(error) = compare.call(
-            &JsValue::undefined(),
-            &[id, JsValue::from(header), JsValue::from(data)],
-            &mut self.context
-        ) {
-            return Err(anyhow!("failed to run compare function: {:#?}", error));
+            for field in fields {
+                cache.update_field(name.clone(), field);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the messages and enums of an encoded `FileDescriptorSet` to the cache.
+    ///
+    /// Enum value names are registered as if by `registerEnum`,
+    /// and are cleared when the matcher is initialized again.
+    #[cfg(feature = "descriptor")]
+    pub fn identify_from_schema(&mut self, descriptor: &[u8]) -> Result<()> {
+        let descriptor = schema::parse_descriptor(descriptor)?;
+
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, fields) in descriptor.messages {
+            info!("Loaded descriptor message '{}' with {} fields", name, fields.len());
+
+            for field in fields {
+                cache.update_field(name.clone(), field);
+            }
+        }
+        drop(cache);
+
+        let mut enums = self.enums.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, fields) in descriptor.enums {
+            enums.entry(name).or_default().extend(fields);
+        }
+        drop(enums);
+
+        // Notify listeners of the new fields.
+        self.notify();
+
+        Ok(())
+    }
+
+    /// Spawns workers and partitions the scripts between them.
+    fn spawn_workers(
+        &mut self,
+        worker_count: usize,
+        path: &Path,
+        scripts: Vec<PathBuf>,
+        host: &HostState
+    ) -> Result<()> {
+        let mut partitions = vec![vec![]; worker_count];
+        for (index, script) in scripts.into_iter().enumerate() {
+            partitions[index % worker_count].push(script);
+        }
+
+        // Every worker is started, so that all failed scripts are reported.
+        let mut failures = vec![];
+        for (index, scripts) in partitions.into_iter().enumerate() {
+            match Worker::spawn(index, path.to_path_buf(), scripts, host.clone()) {
+                Ok(worker) => self.workers.push(worker),
+                Err(error) => failures.push(error.to_string())
+            }
+        }
+
+        if !failures.is_empty() {
+            self.workers.clear();
+            return Err(anyhow!(failures.join("\n")));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the given task against every group of comparers.
+    ///
+    /// When workers are in use, each worker runs the task in parallel.
+    /// Otherwise, the task is run on the current thread.
+    fn dispatch<R, F>(&mut self, task: F) -> Vec<R>
+    where
+        R: Send + 'static,
+        F: Fn(&mut Vec<Comparer>) -> R + Clone + Send + 'static
+    {
+        if self.workers.is_empty() {
+            return vec![task(&mut self.comparers)];
+        }
+
+        // Send the task to every worker before waiting on any of them.
+        let receivers = self.workers
+            .iter()
+            .map(|worker| worker.execute(task.clone()))
+            .collect::<Vec<_>>();
+
+        // A worker which stopped, such as after a panic, cannot return a result.
+        receivers
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, receiver)| match receiver.recv() {
+                Ok(result) => Some(result),
+                Err(_) => {
+                    error!("Worker {} stopped without returning a result, so its scripts were skipped", index);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// The maximum number of errors kept for each script.
+    const MAX_ERRORS: usize = 16;
+
+    /// Records the outcome of running comparers.
+    ///
+    /// Only the most recent errors of each script are kept.
+    fn record(&mut self, report: CompareReport) {
+        self.metrics.identifications += report.identifications;
+        for script in report.invocations {
+            *self.metrics.invocations.entry(script).or_default() += 1;
+        }
+
+        for error in report.errors {
+            let recent = self.errors.entry(error.script.clone()).or_default();
+            if recent.len() >= Self::MAX_ERRORS {
+                recent.pop_front();
+            }
+
+            recent.push_back(error);
+        }
+    }
+
+    /// Registers a function which is called when the cache is updated.
+    ///
+    /// Listeners are called after each packet has been compared, once for every change.
+    /// Listeners are given a copy of the cache, and a panicking listener does not stop the others.
+    /// The matcher is in use while listeners run, so they must not call back into it.
+    pub fn on_cache_update(&mut self, listener: CacheListener) {
+        self.listeners.0.push(listener);
+    }
+
+    /// Returns the cache which scripts update.
+    ///
+    /// In dry-run mode, this is the scratch cache.
+    fn working_cache(&self) -> &GlobalCache {
+        match self.config.dry_run {
+            true => &self.scratch,
+            false => &self.cache
+        }
+    }
+
+    /// Returns the cache updated by scripts while in dry-run mode.
+    ///
+    /// Returns `None` if `Config::dry_run` is not set.
+    pub fn dry_run_results(&self) -> Option<&GlobalCache> {
+        self.config.dry_run.then_some(&self.scratch)
+    }
+
+    /// Sends all pending cache changes to the listeners.
+    ///
+    /// Changes made by scripts in dry-run mode are not sent, and are kept in `dry_run_changes` instead.
+    /// If the real cache changed in dry-run mode, the scratch cache is copied from it again.
+    fn notify(&mut self) {
+        if self.config.dry_run {
+            let changes = self.scratch.lock().unwrap_or_else(PoisonError::into_inner).take_events();
+            self.dry_run_changes.extend(changes);
+        }
+
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let events = cache.take_events();
+        if events.is_empty() {
+            return;
+        }
+
+        // Keep the scratch cache in step with the real cache.
+        if self.config.dry_run {
+            self.scratch.lock().unwrap_or_else(PoisonError::into_inner).rebase(&cache, &self.dry_run_changes);
+        }
+
+        // Listeners are given a copy of the cache, so the lock is not held while they run.
+        let snapshot = (!self.listeners.0.is_empty()).then(|| cache.clone());
+        drop(cache);
+
+        if let Some(snapshot) = snapshot {
+            for event in &events {
+                for listener in &self.listeners.0 {
+                    let called = panic::catch_unwind(AssertUnwindSafe(|| listener(&snapshot, event)));
+                    if called.is_err() {
+                        error!("Cache listener panicked while handling {:?}", event);
+                    }
+                }
+            }
+        }
+
+        self.history.extend(events);
+        if let Some(limit) = self.config.history_limit {
+            let excess = self.history.len().saturating_sub(limit);
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Returns the most recent changes made to the cache, in the order they were made.
+    ///
+    /// Reverted changes are kept, followed by a `CacheEvent::Undone` event.
+    /// At most `Config::history_limit` events are kept.
+    pub fn history(&self) -> &VecDeque<CacheEvent> {
+        &self.history
+    }
+
+    /// Reverts the most recent change made to the cache, and notifies listeners.
+    ///
+    /// Returns whether there was a change to revert.
+    pub fn undo(&mut self) -> bool {
+        let undone = self.cache.lock().unwrap_or_else(PoisonError::into_inner).undo_last();
+        self.notify();
+
+        undone
+    }
+
+    /// Renames a packet in the cache, and notifies listeners.
+    ///
+    /// Returns whether the packet was renamed.
+    pub fn rename_packet(&mut self, old_name: &str, new_name: &str) -> bool {
+        let renamed = self.cache.lock().unwrap_or_else(PoisonError::into_inner).rename(old_name, new_name);
+        self.notify();
+
+        renamed
+    }
+
+    /// Returns the counters describing the work done by the matcher.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Resets the counters describing the work done by the matcher.
+    ///
+    /// The cache and comparers are left unchanged.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Metrics::default();
+    }
+
+    /// Returns the number of times each unknown packet ID was not matched by any script.
+    pub fn unmatched_ids(&self) -> &HashMap<u16, u64> {
+        &self.unmatched_ids
+    }
+
+    /// Counts a packet which was not matched by any script, if its ID is unknown.
+    fn record_unmatched(&mut self, id: u16) {
+        if !self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).id_known(id) {
+            *self.unmatched_ids.entry(id).or_default() += 1;
+        }
+    }
+
+    /// Renders a human-readable summary of the loaded scripts, cache, and metrics.
+    pub fn describe(&self) -> String {
+        let mut summary = String::new();
+
+        let _ = writeln!(summary, "Scripts: {} loaded", self.scripts.len());
+        for script in &self.scripts {
+            let _ = writeln!(summary, "  {}", script);
+        }
+
+        let _ = writeln!(summary, "Packet names: {}", self.names.join(", "));
+
+        {
+            let cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+            let fields = cache.known_names()
+                .iter()
+                .map(|name| cache.fields(name).map_or(0, <[MessageField]>::len))
+                .sum::<usize>();
+
+            let _ = writeln!(
+                summary, "Cache: {} known packets, {} identified fields",
+                cache.known_names().len(), fields
+            );
+        }
+
+        let errors = self.errors.values().map(VecDeque::len).sum::<usize>();
+        let _ = writeln!(summary, "Recent errors: {}", errors);
+
+        let _ = write!(
+            summary, "Metrics: {} packets, {} decode failures, {} identifications, {} unmatched IDs",
+            self.metrics.packets, self.metrics.decode_failures,
+            self.metrics.identifications, self.unmatched_ids.len()
+        );
+
+        summary
+    }
+
+    /// Returns the most recent errors raised by each script.
+    pub fn recent_errors(&self) -> Vec<ScriptError> {
+        self.errors
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Checks if any comparer could be interested in the given packet ID.
+    ///
+    /// A comparer is not interested if all of its packet names are known to belong to other IDs,
+    /// unless the script sets `ALWAYS_RUN`. Packets whose ID is not in the cache are always of interest.
+    fn interested(&self, id: u16) -> bool {
+        if self.always_run {
+            return true;
+        }
+
+        // Unknown packets are always decoded, so that malformed packets are reported.
+        let cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+        if !cache.id_known(id) {
+            return true;
+        }
+
+        self.names.iter().any(|name| {
+            match cache.id_of(name) {
+                Some(known_id) => known_id == id,
+                None => true
+            }
+        })
+    }
+
+    /// Runs every comparer against the given sample packets.
+    ///
+    /// Each sample is a packet ID, header, and data.
+    ///
+    /// Changes made to the cache are discarded afterwards, and the metrics and errors
+    /// are left unchanged. State kept by the scripts themselves is not reset.
+    ///
+    /// Samples which cannot be decoded are skipped and listed in the summary.
+    pub fn warmup(
+        &mut self,
+        samples: &[(u16, Vec<u8>, Vec<u8>)]
+    ) -> Result<WarmupSummary, BiscuitError> {
+        // Start a report for every script.
+        let mut reports = self.dispatch(|comparers| {
+            comparers
+                .iter()
+                .map(|comparer| comparer.script.clone())
+                .collect::<Vec<_>>()
+        })
+            .concat()
+            .into_iter()
+            .map(|script| (script.clone(), WarmupReport { script, runs: 0, errors: vec![] }))
+            .collect::<HashMap<_, _>>();
+
+        // Keep a copy of the cache to restore afterwards.
+        let snapshot = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let metrics = self.metrics.clone();
+
+        let mut decode_errors = vec![];
+        for (index, (id, header, data)) in samples.iter().enumerate() {
+            let (header, data) = match self.decode(*id, header, data, false) {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    decode_errors.push(SampleError { index, packet_id: *id, message: error.to_string() });
+                    continue;
+                }
+            };
+
+            let id = *id;
+            let cache = self.working_cache().clone();
+            let header = Arc::new(header);
+            let data = Arc::new(data);
+
+            let outcomes = self.dispatch(move |comparers| {
+                compare_all(comparers, &cache, id, Direction::Unknown, &header, &data, false)
+            });
+            for outcome in outcomes {
+                for script in outcome.invocations {
+                    if let Some(report) = reports.get_mut(&script) {
+                        report.runs += 1;
+                    }
+                }
+
+                for error in outcome.errors {
+                    if let Some(report) = reports.get_mut(&error.script) {
+                        report.errors.push(error);
+                    }
+                }
+            }
+        }
+
+        // Discard any changes made by the samples.
+        self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).replace(snapshot);
+        self.metrics = metrics;
+
+        Ok(WarmupSummary {
+            reports: reports.into_values().collect(),
+            decode_errors
+        })
+    }
+
+    /// Runs every comparer against a packet, including those which would normally skip it.
+    ///
+    /// This is intended for finding out why a packet is not being matched.
+    /// Changes made to the cache are discarded afterwards, and the metrics are left unchanged.
+    pub fn probe(
+        &mut self,
+        id: u16,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<Vec<ProbeResult>, BiscuitError> {
+        let metrics = self.metrics.clone();
+        let decoded = self.decode(id, header, data, false);
+        self.metrics = metrics;
+        let (header, data) = decoded?;
+
+        // Keep a copy of the cache to restore afterwards.
+        let snapshot = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).clone();
+
+        let header = Arc::new(header);
+        let data = Arc::new(data);
+        let results = self.dispatch(move |comparers| {
+            comparers
+                .iter_mut()
+                .map(|comparer| {
+                    let result = comparer.try_compare(id, Direction::Unknown, &header, &data);
+                    ProbeResult {
+                        script: comparer.script.clone(),
+                        matched: *result.as_ref().unwrap_or(&false),
+                        error: result.err().map(|error| error.to_string())
+                    }
+                })
+                .collect::<Vec<_>>()
+        }).concat();
+
+        // Discard any changes made by the scripts.
+        self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).replace(snapshot);
+
+        Ok(results)
+    }
+
+    /// Renders a one-line summary of a packet, using the `format` function of its script.
+    ///
+    /// The script is the comparer which identifies the packet's known name.
+    /// The metrics are left unchanged.
+    ///
+    /// Returns `None` if the packet's name is not known,
+    /// or its script does not export `format`.
+    pub fn format(
+        &mut self,
+        id: u16,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<Option<String>, BiscuitError> {
+        // Find the name of the packet.
+        let name = {
+            let cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+            match cache.name_of(id) {
+                Some(name) => name.to_string(),
+                None => return Ok(None)
+            }
+        };
+
+        let metrics = self.metrics.clone();
+        let decoded = self.decode(id, header, data, false);
+        self.metrics = metrics;
+        let (_, data) = decoded?;
+
+        let data = Arc::new(data);
+        let results = self.dispatch(move |comparers| {
+            comparers
+                .iter_mut()
+                .find(|comparer| comparer.names.contains(&name))
+                .map(|comparer| (
+                    comparer.script.clone(),
+                    comparer.format(id, &data).map_err(|error| error.to_string())
+                ))
+        });
+
+        match results.into_iter().flatten().next() {
+            Some((_, Ok(formatted))) => Ok(formatted),
+            Some((script, Err(error))) => Err(BiscuitError::Script {
+                name: script,
+                source: error.into()
+            }),
+            None => Ok(None)
+        }
+    }
+
+    /// Provides the given data to the matcher.
+    ///
+    /// The data is first decoded, then checked against all comparers.
+    ///
+    /// Returns the packet's name if it was newly identified by this packet.
+    pub fn compare(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<Option<String>, BiscuitError> {
+        self.metrics.packets += 1;
+
+        // Skip decoding if no comparer would be run.
+        if !self.interested(id) {
+            self.record_unmatched(id);
+            return Ok(None);
+        }
+
+        let (header, data) = self.decode(id, header, data, true)?;
+        let (_, name) = self.run(id, direction, header, data)?;
+
+        Ok(name)
+    }
+
+    /// Provides raw data to the matcher, describing why it could not be decoded.
+    ///
+    /// Unlike `Matcher::compare`, the packet is decoded even if no script would be run,
+    /// so that decoding problems are always reported.
+    /// A header which cannot be decoded is reported even if `Config::strict_header` is not set.
+    ///
+    /// Errors which are not caused by decoding, such as oversized packets, are still returned as errors.
+    pub fn feed_raw(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: &[u8],
+        data: &[u8]
+    ) -> Result<FeedOutcome, BiscuitError> {
+        self.metrics.packets += 1;
+
+        self.check_size(id, header, data)?;
+
+        let data = match self.decode_data(id, data, true) {
+            Ok(decoded) => decoded,
+            Err(BiscuitError::Decode { message, .. }) => {
+                return Ok(FeedOutcome::BodyDecodeFailed(message));
+            },
+            Err(error) => return Err(error)
+        };
+        let header = match self.decode_header(id, header, true) {
+            Ok(decoded) => decoded,
+            Err(BiscuitError::Decode { message, .. }) => {
+                return Ok(FeedOutcome::HeaderDecodeFailed(message));
+            },
+            Err(error) => return Err(error)
+        };
+
+        // Skip the packet if no comparer would be run.
+        if !self.interested(id) {
+            self.record_unmatched(id);
+            return Ok(FeedOutcome::NoMatch);
+        }
+
+        let (matches, name) = self.run(id, direction, header, data)?;
+        if matches.is_empty() {
+            return Ok(FeedOutcome::NoMatch);
+        }
+
+        let results = matches
+            .into_iter()
+            .map(|script| ScriptMatch {
+                script,
+                name: name.clone()
+            })
+            .collect();
+
+        Ok(FeedOutcome::Matched(results))
+    }
+
+    /// Provides already-decoded data to the matcher.
+    ///
+    /// This skips decoding, but is otherwise the same as `Matcher::compare`.
+    pub fn compare_decoded(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: DecodedMessage,
+        data: DecodedMessage
+    ) -> Result<Option<String>, BiscuitError> {
+        self.metrics.packets += 1;
+
+        // Skip the packet if no comparer would be run.
+        if !self.interested(id) {
+            self.record_unmatched(id);
+            return Ok(None);
+        }
+
+        let (_, name) = self.run(id, direction, header, data)?;
+
+        Ok(name)
+    }
+
+    /// Runs the `decrypt` function of the decrypting script on the data of a packet.
+    ///
+    /// Returns `None` if no script decrypts packets, or the script left the data unchanged.
+    fn decrypt(&mut self, id: u16, data: &[u8]) -> Result<Option<Vec<u8>>, BiscuitError> {
+        let Some(script) = self.decrypter.clone() else {
+            return Ok(None);
+        };
+
+        let data = Arc::new(data.to_vec());
+        let results = {
+            let script = script.clone();
+            self.dispatch(move |comparers| {
+                comparers
+                    .iter_mut()
+                    .find(|comparer| comparer.script == script)
+                    .map(|comparer| comparer.decrypt(id, &data).map_err(|error| error.to_string()))
+            })
+        };
+
+        match results.into_iter().flatten().next() {
+            Some(Ok(decrypted)) => Ok(decrypted),
+            Some(Err(error)) => {
+                self.metrics.decode_failures += 1;
+                Err(BiscuitError::Script {
+                    name: script,
+                    source: error.into()
+                })
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Decodes the header and data of a packet.
+    ///
+    /// Buffers larger than `Config::max_packet_size` are rejected.
+    /// If `decrypt` is set and a script decrypts packets, the data is decrypted before it is decoded.
+    /// This is only set for packets which are input live, since decryption may depend on earlier packets.
+    fn decode(
+        &mut self,
+        id: u16,
+        header: &[u8],
+        data: &[u8],
+        decrypt: bool
+    ) -> Result<(DecodedMessage, DecodedMessage), BiscuitError> {
+        self.check_size(id, header, data)?;
+
+        let data = self.decode_data(id, data, decrypt)?;
+        let header = self.decode_header(id, header, self.config.strict_header)?;
+
+        Ok((header, data))
+    }
+
+    /// Rejects a header or packet larger than `Config::max_packet_size`.
+    fn check_size(&self, id: u16, header: &[u8], data: &[u8]) -> Result<(), BiscuitError> {
+        let Some(limit) = self.config.max_packet_size else {
+            return Ok(());
+        };
+
+        for (part, size) in [("header", header.len()), ("packet", data.len())] {
+            if size > limit {
+                warn!(
+                    packet_id = id;
+                    "Rejected {} of packet {}: {} bytes exceeds the limit of {} bytes",
+                    part, id, size, limit
+                );
+
+                return Err(BiscuitError::PacketTooLarge { part, size, limit });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the data of a packet, decrypting it first if `decrypt` is set.
+    fn decode_data(
+        &mut self,
+        id: u16,
+        data: &[u8],
+        decrypt: bool
+    ) -> Result<DecodedMessage, BiscuitError> {
+        // Decrypt the data, if a script provides a decrypt function.
+        let decrypted = match decrypt {
+            true => self.decrypt(id, data)?,
+            false => None
+        };
+        let data = decrypted.as_deref().unwrap_or(data);
+
+        match protoshark::decode(data) {
+            Ok(decoded) => Ok(DecodedMessage::new(decoded, Some(data.to_vec()))),
+            Err(error) => {
+                self.metrics.decode_failures += 1;
+                Err(BiscuitError::Decode {
+                    part: "packet",
+                    message: wire::diagnose(data).unwrap_or_else(|| format!("{:?}", error))
+                })
+            }
+        }
+    }
+
+    /// Decodes the header of a packet.
+    ///
+    /// If the header cannot be decoded and `strict` is not set,
+    /// an empty header is used instead, since the header may only contain framing bytes.
+    fn decode_header(
+        &mut self,
+        id: u16,
+        header: &[u8],
+        strict: bool
+    ) -> Result<DecodedMessage, BiscuitError> {
+        match protoshark::decode(header) {
+            Ok(decoded) => Ok(DecodedMessage::new(decoded, Some(header.to_vec()))),
+            Err(error) if strict => {
+                self.metrics.decode_failures += 1;
+                Err(BiscuitError::Decode {
+                    part: "header",
+                    message: wire::diagnose(header).unwrap_or_else(|| format!("{:?}", error))
+                })
+            },
+            Err(error) => {
+                self.metrics.decode_failures += 1;
+                debug!(
+                    packet_id = id;
+                    "Failed to decode header of packet {}, using an empty header: {:#?}",
+                    id, error
+                );
+
+                Ok(DecodedMessage::new(ProtoMessage::new(), None))
+            }
+        }
+    }
+
+    /// Runs every comparer against the decoded packet.
+    ///
+    /// Returns the file names of the scripts which reported a match,
+    /// and the packet's name if it was newly identified by this packet.
+    fn run(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: DecodedMessage,
+        data: DecodedMessage
+    ) -> Result<(Vec<String>, Option<String>), BiscuitError> {
+        // Check if the packet is already known.
+        let known = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner).id_known(id);
+
+        // Send the data to each comparer.
+        let cache = self.working_cache().clone();
+        let header = Arc::new(header);
+        let data = Arc::new(data);
+        let stop_on_match = self.config.stop_on_first_match;
+
+        let reports = {
+            let header = header.clone();
+            let data = data.clone();
+            self.dispatch(move |comparers| {
+                compare_all(comparers, &cache, id, direction, &header, &data, stop_on_match)
+            })
+        };
+        let matches = reports
+            .iter()
+            .flat_map(|report| report.matches.iter().cloned())
+            .collect::<Vec<_>>();
+        for report in reports {
+            self.record(report);
+        }
+
+        if matches.is_empty() {
+            self.record_unmatched(id);
+        }
+
+        // Remember the header of the packet, if it has a name.
+        if self.config.retain_headers {
+            let mut cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(name) = cache.name_of(id).map(|name| name.to_string()) {
+                let max_depth = self.config.max_message_depth.unwrap_or(usize::MAX);
+                let fields = header.converted(max_depth, id).to_fields();
+                cache.update_header(name, fields);
+            }
+        }
+
+        // Count the fields present in the packet, if it has a name.
+        if self.config.track_field_presence {
+            let mut cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(name) = cache.name_of(id).map(|name| name.to_string()) {
+                let fields = (&data.message).into_iter().map(|(key, _)| *key as i32).collect::<Vec<_>>();
+                cache.record_presence(name, fields);
+            }
+        }
+
+        // Notify listeners of any changes.
+        self.notify();
+
+        // Check if the packet was identified by a comparer.
+        if known {
+            return Ok((matches, None));
+        }
+
+        let cache = self.working_cache().lock().unwrap_or_else(PoisonError::into_inner);
+        Ok((matches, cache.name_of(id).map(|name| name.to_string())))
+    }
+}
+
+impl Drop for Matcher {
+    fn drop(&mut self) {
+        self.unload();
+    }
+}
+
+/// Creates comparers from the given script files.
+///
+/// Scripts which fail to load are logged and skipped.
+/// If `Config::strict_init` is set, an error listing every failed script is returned instead.
+pub(crate) fn load_comparers(
+    path: &Path,
+    scripts: &[PathBuf],
+    host: &HostState
+) -> Result<Vec<Comparer>> {
+    // Create the module loader.
+    let loader = Rc::new(js_catch!(SimpleModuleLoader::new(path)));
+
+    let mut comparers = vec![];
+    let mut failures = vec![];
+    for entry in scripts {
+        match Comparer::from(&loader, entry, host) {
+            Ok(Some(script)) => comparers.push(script),
+            Err(error) => {
+                let script = entry.to_string_lossy();
+                warn!(
+                    script = script.as_ref();
+                    "Invalid script while parsing '{}' (maybe syntax error?): {}",
+                    script,
+                    error
+                );
+
+                failures.push(format!("script '{}' failed to load: {}", script, error));
+            },
+            _ => continue
+        };
+    }
+
+    if host.config.strict_init && !failures.is_empty() {
+        return Err(anyhow!(failures.join("\n")));
+    }
+
+    Ok(comparers)
+}
+
+/// The outcome of running a group of comparers against a packet.
+#[derive(Debug, Default)]
+struct CompareReport {
+    /// The errors raised by scripts.
+    errors: Vec<ScriptError>,
+
+    /// The file names of the scripts which were run.
+    invocations: Vec<String>,
+
+    /// The number of identifications made by scripts.
+    identifications: u64,
+
+    /// The file names of the scripts which reported a match.
+    matches: Vec<String>
+}
+
+/// Provides the given data to each comparer in the group.
+///
+/// If `stop_on_match` is set, the remaining comparers are skipped once one reports a match.
+fn compare_all(
+    comparers: &mut [Comparer],
+    cache: &GlobalCache,
+    id: u16,
+    direction: Direction,
+    header: &DecodedMessage,
+    data: &DecodedMessage,
+    stop_on_match: bool
+) -> CompareReport {
+    let mut report = CompareReport::default();
+    for comparer in comparers {
+        // Check the cache to see if the packet is known.
+        let cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if !comparer.interested(&cache, id) {
+            continue;
+        }
+
+        // Unlock the cache.
+        drop(cache);
+
+        let identifications = comparer.identifications.get();
+        let matched = match comparer.try_compare(id, direction, header, data) {
+            Ok(matched) => matched,
+            Err(error) => {
+                warn!(
+                    script = comparer.script.as_str(), packet_id = id;
+                    "Script '{}' failed to compare packet {}: {:#?}",
+                    comparer.script, id, error
+                );
+
+                report.errors.push(ScriptError {
+                    script: comparer.script.clone(),
+                    packet_id: id,
+                    message: error.to_string()
+                });
+
+                false
+            }
+        };
+
+        report.invocations.push(comparer.script.clone());
+        report.identifications += comparer.identifications.get() - identifications;
+        if matched {
+            report.matches.push(comparer.script.clone());
+        }
+
+        if stop_on_match && matched {
+            break;
+        }
+    }
+
+    report
+}
+
+#[derive(Debug)]
+pub struct Comparer {
+    context: Context,
+
+    /// The file name of the script.
+    pub script: String,
+
+    /// The packet names this comparer can identify.
+    pub names: Vec<String>,
+
+    /// Whether this comparer is run even when its packet names are known to belong to other IDs.
+    pub always_run: bool,
+
+    /// Whether the script sets `DECRYPTS` and exports a `decrypt` function.
+    pub decrypts: bool,
+
+    /// The number of identifications made by this comparer.
+    identifications: Rc<Cell<u64>>
+}
+
+/// This unsafe implementation is used to allow any comparers to be sent between threads.
+///
+/// A JavaScript `Context` is not thread-safe, so ensure that it is always being called on the same thread.
+unsafe impl Send for Comparer {}
+
+impl Comparer {
+    /// The name of the environment variables global property.
+    const ENV_VARS_NAME: JsString = js_string!("env");
+
+    /// Creates a script instance from the contents of script.
+    pub fn from(
+        loader: &Rc<SimpleModuleLoader>,
+        script: &Path,
+        host: &HostState
+    ) -> Result<Option<Self>> {
+        // Parse the script.
+        let contents = host.sources.read(script)?;
+        let source = Source::from_bytes(&*contents).with_path(script);
+
+        let (mut context, identifications) = Self::create_context(loader, script, host)?;
+
+        // Load the script into the context.
+        if host.config.module_scripts {
+            Self::evaluate_module(&mut context, loader, script, &contents)?;
+        } else if let Err(error) = context.eval(source) {
+            return Err(anyhow!("failed to evaluate script: {:#?}", error));
+        };
+
+        // If the script does not contain a 'compare' or 'compareHeader' function, return `None`.
+        if js_get!(context, "compare"; as_callable).is_err() &&
+            js_get!(context, "compareHeader"; as_callable).is_err() {
+            return Ok(None);
+        }
+        
+        // Get the script's packet names.
+        // If multiple names aren't declared, fall back to a single name.
+        let names = match js_get!(context, "PACKET_NAMES"; as_object) {
+            Ok(names) => {
+                let names = js_catch!(JsArray::from_object(names));
+                let length = js_catch!(names.length(&mut context));
+
+                let mut result = vec![];
+                for index in 0..length {
+                    let name = js_catch!(names.get(index, &mut context));
+                    let Some(name) = name.as_string() else {
+                        return Err(anyhow!("packet names must be strings"));
+                    };
+
+                    result.push(name.to_std_string_escaped());
+                }
+
+                // A script without names could never match a packet.
+                if result.is_empty() {
+                    return Err(anyhow!("PACKET_NAMES must contain at least one name"));
+                }
+
+                result
+            },
+            Err(_) => {
+                let Ok(name) = js_get!(context, "PACKET_NAME"; as_string) else {
+                    return Err(anyhow!("failed to get packet name"));
+                };
+
+                vec![name.to_std_string_escaped()]
+            }
+        };
+
+        // Run the initialize function if it exists.
+        // It is provided the environment variables and a handle to the live cache.
+        if let Ok(initialize) = js_get!(context, "init"; as_callable) {
+            let env = js_catch!(context.global_object().get(Self::ENV_VARS_NAME, &mut context));
+            let cache = JsValue::from(cache_handle(&mut context));
+
+            js_catch!(initialize.call(
+                &JsValue::undefined(),
+                &[env, cache],
+                &mut context
+            ));
+        }
+
+        // Get the script's file name.
+        let script = script
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // Check if the script opted out of being skipped for known packets.
+        let always_run = js_get!(context, "ALWAYS_RUN"; as_boolean).unwrap_or(false);
+
+        // Check if the script decrypts packets.
+        // A `decrypt` function is only used if the script opts in.
+        let decrypts = js_get!(context, "DECRYPTS"; as_boolean).unwrap_or(false);
+        if decrypts && js_get!(context, "decrypt"; as_callable).is_err() {
+            return Err(anyhow!("DECRYPTS is set, but the script does not export a decrypt function"));
+        }
+
+        Ok(Some(Comparer { context, script, names, always_run, decrypts, identifications }))
+    }
+
+    /// Creates a script context with the runtime functions and host state.
+    ///
+    /// Returns the context and its identification counter.
+    fn create_context(
+        loader: &Rc<SimpleModuleLoader>,
+        script: &Path,
+        host: &HostState
+    ) -> Result<(Context, Rc<Cell<u64>>)> {
+        // Create a script context.
+        let mut context = js_catch!(Context::builder()
+            .module_loader(loader.clone())
+            .build());
+
+        // Limit the iterations of each loop in the script, if configured.
+        if let Some(iterations) = host.config.max_loop_iterations {
+            context
+                .runtime_limits_mut()
+                .set_loop_iteration_limit(iterations);
+        }
+
+        // Limit the depth of recursion in the script, if configured.
+        if let Some(depth) = host.config.max_recursion_depth {
+            context
+                .runtime_limits_mut()
+                .set_recursion_limit(depth);
+        }
+        if let Some(size) = host.config.max_stack_size {
+            context
+                .runtime_limits_mut()
+                .set_stack_size_limit(size);
+        }
+
+        // Add the cache to the realm.
+        let realm = context.realm().clone();
+        realm
+            .host_defined_mut()
+            .insert(JsCache(host.cache.clone()));
+
+        // Add the identification counter to the realm.
+        let identifications = Rc::new(Cell::new(0));
+        realm
+            .host_defined_mut()
+            .insert(JsStats(identifications.clone()));
+
+        // Add the recent identifications to the realm.
+        let generation = host.cache.lock().unwrap_or_else(PoisonError::into_inner).generation();
+        realm
+            .host_defined_mut()
+            .insert(JsIdentified::new(generation));
+
+        // Add the environment variables to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsEnv(host.env_vars.clone()));
+
+        // Add the script log level to the realm.
+        // This is validated when the matcher is initialized.
+        let log_level = host.config.script_log_level
+            .parse::<LevelFilter>()
+            .unwrap_or(LevelFilter::Trace);
+        realm
+            .host_defined_mut()
+            .insert(JsLogLevel(log_level));
+
+        // Add the random number generator to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsRandom::new(host.config.rng_seed));
+
+        // Add the shared state to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsShared(host.shared.clone()));
+
+        // Add the enum names to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsEnums(host.enums.clone()));
+
+        // Add the files loaded with `requireOnce` to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsSharedModules(host.modules.clone()));
+
+        // Add the configuration to the realm.
+        realm
+            .host_defined_mut()
+            .insert(JsConfig(host.config.clone()));
+        
+        // Add the script's directory to the realm.
+        let directory = script.parent().map(Path::to_path_buf);
+        realm
+            .host_defined_mut()
+            .insert(JsModules::new(directory.unwrap_or_default()));
+
+        // Add the environment variables to the context.
+        let map = JsMap::new(&mut context);
+        for (key, value) in host.env_vars.iter() {
+            let key = js_string!(key.clone());
+            let value = js_string!(value.clone());
+            js_catch!(map.set(key, value, &mut context));
+        }
+
+        // Update the runtime.
+        context
+            .register_global_property(Self::ENV_VARS_NAME, map.clone(), Attribute::all())
+            .expect("global property 'console' already exists");
+
+        declare_runtime(realm, &mut context)?;
+
+        Ok((context, identifications))
+    }
+
+    /// Runs a script once after all comparers have loaded.
+    ///
+    /// The script is given the packet names of every loaded comparer as `LOADED_NAMES`.
+    /// If the script throws, the error is returned.
+    pub(crate) fn run_post_load(
+        path: &Path,
+        script: &Path,
+        names: &[String],
+        host: &HostState
+    ) -> Result<()> {
+        let loader = Rc::new(js_catch!(SimpleModuleLoader::new(path)));
+        let (mut context, _) = Self::create_context(&loader, script, host)?;
+
+        // Provide the names of the loaded comparers.
+        let loaded = JsArray::new(&mut context);
+        for name in names {
+            js_catch!(loaded.push(js_string!(name.clone()), &mut context));
+        }
+        js_catch!(loaded.set_integrity_level(IntegrityLevel::Frozen, &mut context));
+
+        context
+            .register_global_property(js_string!("LOADED_NAMES"), loaded, Attribute::all())
+            .expect("global property 'LOADED_NAMES' already exists");
+
+        // Run the script.
+        let contents = host.sources.read(script)?;
+        let source = Source::from_bytes(&*contents).with_path(script);
+        if let Err(error) = context.eval(source) {
+            return Err(anyhow!("failed to run post-load script: {:#?}", error));
+        }
+
+        Ok(())
+    }
+
+    /// Provides the given data to the comparer, catching any panics.
+    ///
+    /// A panicking script should not take down the other scripts.
+    pub fn try_compare(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: &DecodedMessage,
+        data: &DecodedMessage
+    ) -> Result<bool> {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            self.compare(id, direction, header, data)
+        }))
+            .unwrap_or_else(|_| Err(anyhow!("script panicked while comparing packet")))
+    }
+
+    /// Loads a script as an ES module.
+    ///
+    /// The module's exports are copied onto the global object,
+    /// so they can be found in the same way as a classic script's functions.
+    fn evaluate_module(
+        context: &mut Context,
+        loader: &Rc<SimpleModuleLoader>,
+        script: &Path,
+        contents: &[u8]
+    ) -> Result<()> {
+        let source = Source::from_bytes(contents).with_path(script);
+        let module = match Module::parse(source, None, context) {
+            Ok(module) => module,
+            Err(error) => return Err(anyhow!("failed to parse module: {:#?}", error))
+        };
+
+        // Register the module, so other modules importing it share the same instance.
+        loader.insert(script.to_path_buf(), module.clone());
+
+        // Evaluate the module, including any imports.
+        let promise = module.load_link_evaluate(context);
+        context.run_jobs();
+
+        match promise.state() {
+            PromiseState::Fulfilled(_) => {},
+            PromiseState::Rejected(error) => {
+                let error = error.to_string(context)
+                    .map(|error| error.to_std_string_escaped())
+                    .unwrap_or_else(|_| "unknown error".to_string());
+
+                return Err(anyhow!("failed to evaluate module: {}", error));
+            },
+            PromiseState::Pending => return Err(anyhow!("module did not finish evaluating"))
+        }
+
+        // Expose the module's exports as globals.
+        let namespace = module.namespace(context);
+        for key in js_catch!(namespace.own_property_keys(context)) {
+            let value = js_catch!(namespace.get(key.clone(), context));
+            js_catch!(context.global_object().set(key, value, false, context));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the script's decrypt function on the data of a packet.
+    ///
+    /// The function is given the packet ID and the data as an `ArrayBuffer`,
+    /// and returns the decrypted data, or `undefined` to leave the data unchanged.
+    pub fn decrypt(&mut self, id: u16, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let Ok(decrypt) = js_get!(self.context, "decrypt"; as_callable) else {
+            return Ok(None);
+        };
+
+        let buffer = js_catch!(JsArrayBuffer::from_byte_block(data.to_vec(), &mut self.context));
+        let result = match decrypt.call(
+            &JsValue::undefined(),
+            &[JsValue::from(id), JsValue::from(buffer)],
+            &mut self.context
+        ) {
+            Ok(result) => result,
+            Err(error) => return Err(anyhow!("failed to run decrypt function: {:#?}", error))
+        };
+
+        if result.is_undefined() {
+            return Ok(None);
+        }
+
+        match utils::js_bytes(&result) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) => Err(anyhow!("decrypt function must return an ArrayBuffer: {:#?}", error))
+        }
+    }
+
+    /// Runs the script's format function on a packet.
+    ///
+    /// The function is given the packet ID and data, and returns a string.
+    ///
+    /// Returns `None` if the script does not export `format`, or it did not return a string.
+    pub fn format(&mut self, id: u16, data: &DecodedMessage) -> Result<Option<String>> {
+        let Ok(format) = js_get!(self.context, "format"; as_callable) else {
+            return Ok(None);
+        };
+
+        let data = SerializedMessage::from_to_js(&mut self.context, data, id)?;
+        let result = match format.call(
+            &JsValue::undefined(),
+            &[JsValue::from(id), JsValue::from(data)],
+            &mut self.context
+        ) {
+            Ok(result) => result,
+            Err(error) => return Err(anyhow!("failed to run format function: {:#?}", error))
+        };
+
+        Ok(result.as_string().map(|string| string.to_std_string_escaped()))
+    }
+
+    /// Runs the script's teardown function if it exists.
+    ///
+    /// This is called when the comparer is unloaded.
+    fn teardown(&mut self) -> Result<()> {
+        if let Ok(teardown) = js_get!(self.context, "teardown"; as_callable) {
+            js_catch!(teardown.call(&JsValue::undefined(), &[], &mut self.context));
         }
 
         Ok(())
     }
+
+    /// Checks if this comparer could be interested in the given packet ID.
+    ///
+    /// A comparer is not interested if all of its packet names are known to belong to other IDs,
+    /// unless the script sets `ALWAYS_RUN`.
+    pub fn interested(&self, cache: &Cache, id: u16) -> bool {
+        if self.always_run {
+            return true;
+        }
+
+        self.names.iter().any(|name| {
+            match cache.id_of(name) {
+                Some(known_id) => known_id == id,
+                None => true
+            }
+        })
+    }
+
+    /// Provides the given data to the comparer.
+    ///
+    /// This will run the comparer's logic and return the result.
+    ///
+    /// The header is first given to the script's `compareHeader` function, if it exists,
+    /// and then the whole packet is given to its `compare` function, if it exists.
+    ///
+    /// Returns whether the script reported a match by returning `true`.
+    pub fn compare(
+        &mut self,
+        id: u16,
+        direction: Direction,
+        header: &DecodedMessage,
+        data: &DecodedMessage
+    ) -> Result<bool> {
+        // Convert parameters into JavaScript objects.
+        let header = SerializedMessage::from_to_js(&mut self.context, header, id)?;
+        let data = SerializedMessage::from_to_js(&mut self.context, data, id)?;
+        let id = js_catch!(id.try_into_js(&mut self.context));
+        let direction = JsValue::from(js_string!(direction.as_str()));
+
+        // Run the header compare function, which is used for routing.
+        let mut matched = false;
+        if let Ok(compare_header) = js_get!(self.context, "compareHeader"; as_callable) {
+            let result = match compare_header.call(
+                &JsValue::undefined(),
+                &[id.clone(), JsValue::from(header.clone())],
+                &mut self.context
+            ) {
+                Ok(result) => result,
+                Err(error) => return Err(anyhow!("failed to run compareHeader function: {:#?}", error))
+            };
+
+            matched = result.as_boolean() == Some(true);
+        }
+
+        // Find the compare function.
+        // If it doesn't exist, only the header is compared.
+        let Ok(compare) = js_get!(self.context, "compare"; as_callable) else {
+            return Ok(matched);
+        };
+
+        // Run the compare function.
+        let result = match compare.call(
+            &JsValue::undefined(),
+            &[id, JsValue::from(header), JsValue::from(data), direction],
+            &mut self.context
+        ) {
+            Ok(result) => result,
+            Err(error) => return Err(anyhow!("failed to run compare function: {:#?}", error))
+        };
+
+        Ok(matched || result.as_boolean() == Some(true))
+    }
+}
+
+impl Drop for Comparer {
+    fn drop(&mut self) {
+        // Comparers are only dropped on the thread which owns their context.
+        // Otherwise, the matcher leaks them instead.
+        if let Err(error) = self.teardown() {
+            warn!(
+                script = self.script.as_str();
+                "Failed to tear down script '{}': {:#?}",
+                self.script, error
+            );
+        }
+    }
 }
 
 /// Adds functions to the JavaScript context.
-fn declare_runtime(_: Realm, context: &mut Context) -> Result<()> {
+fn declare_runtime(realm: Realm, context: &mut Context) -> Result<()> {
+    // Fetch the configuration from the realm.
+    let config = match realm.host_defined().get::<JsConfig>() {
+        Some(config) => config.0.clone(),
+        None => return Err(anyhow!("failed to get configuration from realm"))
+    };
+
+    // Expose a frozen copy of the configuration.
+    let config = js_catch!(serde_json::to_value(config.as_ref()));
+    let config = js_catch!(JsValue::from_json(&config, context));
+    if let Some(object) = config.as_object() {
+        js_catch!(object.set_integrity_level(IntegrityLevel::Frozen, context));
+    }
+
     let console = Console::init(context);
     let module = {
         let obj = JsObject::default();
@@ -344,83 +2132,478 @@ fn declare_runtime(_: Realm, context: &mut Context) -> Result<()> {
     context
         .register_global_property(js_string!("module"), JsValue::from(module), Attribute::default())
         .expect("global property 'module' should not exist");
+    // This global is configurable, so scripts may still declare their own `config` with `let` or `const`.
+    context
+        .register_global_property(js_string!("config"), config, Attribute::all())
+        .expect("global property 'config' should not exist");
+
+    // This map lives as long as the context, so values persist between packets.
+    // Like `config`, this global is configurable so scripts may declare their own `store`.
+    let store = JsMap::new(context);
+    context
+        .register_global_property(js_string!("store"), store, Attribute::all())
+        .expect("global property 'store' should not exist");
+
+    // This object is backed by a map shared between all scripts.
+    // It is also configurable, so scripts may declare their own `shared`.
+    let shared = ObjectInitializer::new(context)
+        .function(NativeFunction::from_fn_ptr(js_shared_get), js_string!("get"), 1)
+        .function(NativeFunction::from_fn_ptr(js_shared_set), js_string!("set"), 2)
+        .build();
+    context
+        .register_global_property(js_string!("shared"), shared, Attribute::all())
+        .expect("global property 'shared' should not exist");
     context
         .register_global_class::<SerializedMessage>()
         .expect("class SerializedMessage should not exist");
 
     js_catch!(context.register_global_builtin_callable(
-        js_string!("require"), 0,
-        NativeFunction::from_fn_ptr(js_require)
+        js_string!("require"), 0,
+        NativeFunction::from_fn_ptr(js_require)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        js_string!("requireOnce"), 1,
+        NativeFunction::from_fn_ptr(js_require_once)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("info"), 1,
+        NativeFunction::from_fn_ptr(utils::js_info)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("warn"), 1,
+        NativeFunction::from_fn_ptr(utils::js_warn)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("error"), 1,
+        NativeFunction::from_fn_ptr(utils::js_error)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("base64Decode"), 1,
+        NativeFunction::from_fn_ptr(utils::js_base64_decode)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("rsaDecrypt"), 2,
+        NativeFunction::from_fn_ptr(utils::js_rsa_decrypt)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("aesGcmDecrypt"), 3,
+        NativeFunction::from_fn_ptr(utils::js_aes_gcm_decrypt)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("verifySignature"), 4,
+        NativeFunction::from_fn_ptr(utils::js_verify_signature)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("getEnv"), 1,
+        NativeFunction::from_fn_ptr(utils::js_get_env)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("getEnvNumber"), 1,
+        NativeFunction::from_fn_ptr(utils::js_get_env_number)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("getEnvBool"), 1,
+        NativeFunction::from_fn_ptr(utils::js_get_env_bool)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("now"), 0,
+        NativeFunction::from_fn_ptr(utils::js_now)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("random"), 0,
+        NativeFunction::from_fn_ptr(utils::js_random)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("readVarInt"), 2,
+        NativeFunction::from_fn_ptr(utils::js_read_varint)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("crc32"), 1,
+        NativeFunction::from_fn_ptr(utils::js_crc32)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("crc16"), 1,
+        NativeFunction::from_fn_ptr(utils::js_crc16)
     ));
 
     js_catch!(context.register_global_builtin_callable(
-        JsString::from("info"), 1,
-        NativeFunction::from_fn_ptr(utils::js_info)
+        JsString::from("decodePacket"), 1,
+        NativeFunction::from_fn_ptr(utils::js_decode_packet)
     ));
 
     js_catch!(context.register_global_builtin_callable(
-        JsString::from("warn"), 1,
-        NativeFunction::from_fn_ptr(utils::js_warn)
+        JsString::from("decodeUtf8"), 1,
+        NativeFunction::from_fn_ptr(utils::js_decode_utf8)
     ));
 
     js_catch!(context.register_global_builtin_callable(
-        JsString::from("error"), 1,
-        NativeFunction::from_fn_ptr(utils::js_error)
+        JsString::from("decodeLatin1"), 1,
+        NativeFunction::from_fn_ptr(utils::js_decode_latin1)
     ));
 
     js_catch!(context.register_global_builtin_callable(
-        JsString::from("base64Decode"), 1,
-        NativeFunction::from_fn_ptr(utils::js_base64_decode)
+        JsString::from("registerEnum"), 3,
+        NativeFunction::from_fn_ptr(js_register_enum)
     ));
 
     js_catch!(context.register_global_builtin_callable(
-        JsString::from("rsaDecrypt"), 2,
-        NativeFunction::from_fn_ptr(utils::js_rsa_decrypt)
+        JsString::from("inferType"), 1,
+        NativeFunction::from_fn_ptr(utils::js_infer_type)
+    ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("firstSeen"), 1,
+        NativeFunction::from_fn_ptr(js_first_seen)
     ));
 
     js_catch!(context.register_global_builtin_callable(
         JsString::from("identify"), 3,
         NativeFunction::from_fn_ptr(js_identify)
     ));
+
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("identifyAll"), 3,
+        NativeFunction::from_fn_ptr(js_identify_all)
+    ));
     
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("guessName"), 2,
+        NativeFunction::from_fn_ptr(js_guess_name)
+    ));
+
     js_catch!(context.register_global_builtin_callable(
         JsString::from("isKnown"), 1,
         NativeFunction::from_fn_ptr(js_is_known)
     ));
 
+    js_catch!(context.register_global_builtin_callable(
+        JsString::from("fieldKnown"), 3,
+        NativeFunction::from_fn_ptr(js_field_known)
+    ));
+
     Ok(())
 }
 
 /// JavaScript-compatible function that includes a file in the context.
+///
+/// Paths are resolved relative to the script's directory.
+/// Required files are given their own `require`, which resolves paths relative to the file.
 fn js_require(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let directory = script_directory(context)?;
+    require(&directory, args, context)
+}
+
+/// Includes a file in the context, resolving its path against the given directory.
+///
+/// Each file is only evaluated once, after which its exports are reused.
+///
+/// Each file starts with an empty object as `module.exports`, and the requiring file's exports are restored afterward.
+/// If files require each other, `require` returns the partial exports of the file which is still being evaluated.
+///
+/// JavaScript files are evaluated inside of a function, like CommonJS modules,
+/// so their declarations are not added to the global object.
+fn require(directory: &Path, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
     // Fetch the file path from the arguments.
     let file = args.get_or_undefined(0);
     let file = file.to_string(context)?.to_std_string_escaped();
     
-    // Get the parent path from the realm.
+    // Resolve the path against the requiring file's directory.
+    let import_file = resolve_require(context, directory, &file)?;
+
+    // Check if the file was already evaluated.
+    {
+        let realm = context.realm().clone();
+        let realm = realm.host_defined_mut();
+        let modules = from_realm!(realm => JsModules);
+
+        let key = import_file.to_string_lossy().to_string();
+        if let Some(exports) = modules.exports.get(&key) {
+            return Ok(exports.clone());
+        }
+    }
+
+    // JSON files are parsed and returned directly.
+    let exports = if import_file.extension().is_some_and(|extension| extension == "json") {
+        let Ok(content) = std::fs::read_to_string(&import_file) else {
+            return js_error!("failed to load file");
+        };
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+            return js_error!("failed to parse JSON file");
+        };
+
+        JsValue::from_json(&json, context)?
+    } else {
+        let Ok(contents) = std::fs::read_to_string(&import_file) else {
+            return js_error!("failed to load file");
+        };
+
+        // The file is given functions which resolve paths against its own directory,
+        // so functions it defines keep requiring files relative to it when called later.
+        let directory = import_file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let functions = [
+            bound_require(&directory, false, context),
+            bound_require(&directory, true, context)
+        ];
+
+        // Get the module object.
+        let global = context.global_object();
+        let module = global.get(js_string!("module"), context)?;
+        let Some(module) = module.as_object() else {
+            return js_error!("failed to get module object");
+        };
+
+        // Give the file its own exports, keeping the requiring file's exports.
+        let previous = module.get(js_string!("exports"), context)?;
+        let partial = JsObject::with_object_proto(context.intrinsics());
+        module.set(js_string!("exports"), partial.clone(), false, context)?;
+
+        // Files which require this file while it is being evaluated are given its partial exports,
+        // as in CommonJS, instead of evaluating it again.
+        let key = import_file.to_string_lossy().to_string();
+        modules_mut(context, |modules| {
+            modules.exports.insert(key.clone(), partial.into());
+        })?;
+
+        // The file is compiled with the `Function` constructor rather than evaluated as a script,
+        // since evaluating a script while a script function is running corrupts boa's call frames.
+        let parameters = [js_string!("require"), js_string!("requireOnce"), JsString::from(contents.as_str())];
+        let result = context.intrinsics()
+            .constructors()
+            .function()
+            .constructor()
+            .construct(&parameters.map(JsValue::from), None, context)
+            .and_then(|wrapper| wrapper.call(&JsValue::undefined(), &functions, context));
+
+        let exports = module.get(js_string!("exports"), context)?;
+        module.set(js_string!("exports"), previous, false, context)?;
+
+        // Files which failed to evaluate are evaluated again if they are required again.
+        if let Err(error) = result {
+            modules_mut(context, |modules| {
+                modules.exports.remove(&key);
+            })?;
+
+            return Err(error);
+        }
+
+        exports
+    };
+
+    // Remember the exports for future requires.
+    let key = import_file.to_string_lossy().to_string();
+    modules_mut(context, |modules| {
+        modules.exports.insert(key, exports.clone());
+    })?;
+
+    Ok(exports)
+}
+
+/// Creates a `require` or `requireOnce` function which resolves paths against the given directory.
+fn bound_require(directory: &Path, once: bool, context: &mut Context) -> JsValue {
+    let directory = js_string!(directory.to_string_lossy().to_string());
+    let function = NativeFunction::from_copy_closure_with_captures(
+        move |_, args, directory, context| {
+            let directory = PathBuf::from(directory.to_std_string_escaped());
+            match once {
+                true => require_once(&directory, args, context),
+                false => require(&directory, args, context)
+            }
+        },
+        directory
+    );
+
+    FunctionObjectBuilder::new(context.realm(), function)
+        .name(if once { "requireOnce" } else { "require" })
+        .length(1)
+        .build()
+        .into()
+}
+
+/// Fetches the directory of the script which owns the context.
+fn script_directory(context: &mut Context) -> JsResult<PathBuf> {
     let realm = context.realm().clone();
     let realm = realm.host_defined_mut();
-    
-    let Some(parent) = realm.get::<String>() else {
-        return js_error!("failed to get parent path");
+    let modules = from_realm!(realm => JsModules);
+
+    Ok(modules.directory.clone())
+}
+
+/// Resolves a path passed to `require` against the directory of the requiring file.
+///
+/// Unless `Config::allow_require_outside_root` is set,
+/// paths outside of the script's directory are rejected.
+fn resolve_require(context: &mut Context, directory: &Path, file: &str) -> JsResult<PathBuf> {
+    let realm = context.realm().clone();
+    let realm = realm.host_defined_mut();
+    let modules = from_realm!(realm => JsModules);
+
+    let import_file = absolute(&directory.join(file));
+
+    // Prevent scripts from reading files outside of the script directory.
+    let allow_outside = from_realm!(realm => JsConfig).0.allow_require_outside_root;
+    if !allow_outside && !import_file.starts_with(&modules.root) {
+        return js_error!(format!("cannot require '{}' from outside of the script directory", file));
+    }
+
+    Ok(import_file)
+}
+
+/// Resolves a path to an absolute path, following symbolic links if the path exists.
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+
+    let path = match std::env::current_dir() {
+        Ok(directory) => directory.join(path),
+        Err(_) => path.to_path_buf()
+    };
+
+    normalize(&path)
+}
+
+/// JavaScript-compatible function that imports a file once for all scripts.
+///
+/// The first script to require a file evaluates it with `require`.
+/// Every script, including the first, is given a copy of its exports, converted through JSON,
+/// so exports which are not JSON-compatible, such as functions, are rejected.
+///
+/// Each file is only evaluated once until the library is initialized again.
+/// If it failed to evaluate, every script is given the same error.
+fn js_require_once(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let directory = script_directory(context)?;
+    require_once(&directory, args, context)
+}
+
+/// Imports a file once for all scripts, resolving its path against the given directory.
+fn require_once(directory: &Path, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let file = args.get_or_undefined(0);
+    let file = file.to_string(context)?.to_std_string_escaped();
+
+    let import_file = resolve_require(context, directory, &file)?;
+    let key = import_file.to_string_lossy().to_string();
+
+    // Fetch the shared exports from the realm.
+    let modules = {
+        let realm = context.realm().clone();
+        let realm = realm.host_defined_mut();
+        from_realm!(realm => JsSharedModules).0.clone()
+    };
+
+    // Check if another script already evaluated the file.
+    match modules.begin(&key) {
+        Ok(Some(exports)) => return JsValue::from_json(&exports, context),
+        Ok(None) => {},
+        Err(error) => return js_error!(error)
+    }
+
+    let shared = match require(directory, args, context) {
+        Ok(exports) if exports.is_undefined() => Ok(serde_json::Value::Null),
+        Ok(exports) => shared_json(&exports, "module.exports", context),
+        Err(error) => Err(format!("failed to evaluate '{}': {}", file, error))
     };
-    let parent = PathBuf::from(parent);
+    modules.finish(&key, shared.clone());
+
+    match shared {
+        Ok(exports) => JsValue::from_json(&exports, context),
+        Err(error) => js_error!(error)
+    }
+}
+
+/// Converts a value shared by `requireOnce` into JSON.
+///
+/// Returns an error naming the first value which cannot be represented in JSON.
+fn shared_json(value: &JsValue, path: &str, context: &mut Context) -> Result<serde_json::Value, String> {
+    let unsupported = |kind: &str| Err(format!("requireOnce cannot share {} at {}", kind, path));
+
+    match value {
+        JsValue::Null => Ok(serde_json::Value::Null),
+        JsValue::Undefined => unsupported("undefined"),
+        JsValue::Boolean(value) => Ok((*value).into()),
+        JsValue::String(value) => Ok(value.to_std_string_escaped().into()),
+        JsValue::Rational(value) => Ok((*value).into()),
+        JsValue::Integer(value) => Ok((*value).into()),
+        JsValue::BigInt(_) => unsupported("a bigint"),
+        JsValue::Symbol(_) => unsupported("a symbol"),
+        JsValue::Object(object) if object.is_callable() => unsupported("a function"),
+        JsValue::Object(object) if object.is_array() => {
+            let array = JsArray::from_object(object.clone()).map_err(|error| error.to_string())?;
+            let length = array.length(context).map_err(|error| error.to_string())?;
+
+            let mut values = vec![];
+            for index in 0..length {
+                let value = array.get(index, context).map_err(|error| error.to_string())?;
+                values.push(shared_json(&value, &format!("{}[{}]", path, index), context)?);
+            }
+
+            Ok(serde_json::Value::Array(values))
+        },
+        JsValue::Object(object) => {
+            let keys = object.own_property_keys(context).map_err(|error| error.to_string())?;
+
+            let mut map = serde_json::Map::new();
+            for key in keys {
+                let name = match &key {
+                    PropertyKey::String(name) => name.to_std_string_escaped(),
+                    PropertyKey::Index(index) => index.get().to_string(),
+                    PropertyKey::Symbol(_) => return unsupported("a symbol key")
+                };
+
+                let value = object.get(key, context).map_err(|error| error.to_string())?;
+                let value = shared_json(&value, &format!("{}.{}", path, name), context)?;
+                map.insert(name, value);
+            }
+
+            Ok(serde_json::Value::Object(map))
+        }
+    }
+}
+
+/// Runs the given function with the realm's module state.
+fn modules_mut(context: &mut Context, func: impl FnOnce(&mut JsModules)) -> JsResult<()> {
+    let realm = context.realm().clone();
+    let mut realm = realm.host_defined_mut();
 
-    // Load the file from the file system.
-    let import_file = parent.join(file);
-    let Ok(source) = Source::from_filepath(&import_file) else {
-        return js_error!("failed to load file");
+    let Some(modules) = realm.get_mut::<JsModules>() else {
+        return js_error!("failed to get JsModules");
     };
-    context.eval(source)?;
+    func(modules);
 
-    // Get the module's exports.
-    let global = context.global_object();
-    let module = global.get(js_string!("module"), context)?;
+    Ok(())
+}
 
-    match module.as_object() {
-        Some(module) => module.get(js_string!("exports"), context),
-        None => js_error!("failed to get module object")
+/// Resolves `.` and `..` segments of a path without accessing the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                if !result.pop() {
+                    result.push(component);
+                }
+            },
+            _ => result.push(component)
+        }
     }
+
+    result
 }
 
 /// JavaScript-compatible function that identifies a packet and its fields.
@@ -428,15 +2611,8 @@ fn js_identify(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult
     let realm = context.realm().clone();
     let realm = realm.host_defined_mut();
 
-    // Fetch the cache from the realm.
-    let Ok(mut cache) = from_realm!(realm => JsCache).0.lock() else {
-        return Err(JsNativeError::typ()
-            .with_message("failed to get cache")
-            .into());
-    };
-
     // Get the data from the arguments.
-    let Some(packet_name) = args.get(0) else {
+    let Some(packet_name) = args.first() else {
         return js_error!("missing packet name argument");
     };
     let Some(packet_id) = args.get(1) else {
@@ -445,14 +2621,165 @@ fn js_identify(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult
     let Some(field) = args.get(2) else {
         return js_error!("missing field argument");
     };
+    let confidence = args.get_or_undefined(3);
+
+    // Convert the data into Rust-owned values.
+    let packet_name = js_convert!(packet_name, as_string).to_std_string_escaped();
+    let packet_id = js_convert!(packet_id, as_number) as u16;
+    let mut packet_field = MessageField::try_from_js(field, context)?;
+
+    // Apply the confidence, if one was provided.
+    if let Some(confidence) = confidence.as_number() {
+        packet_field.confidence = Some(confidence.clamp(0.0, 1.0));
+    }
+
+    // Check the field's type, which would otherwise break proto export.
+    if let Err(error) = packet_field.validate() {
+        if from_realm!(realm => JsConfig).0.strict_field_types {
+            return js_error!(error);
+        }
+
+        warn!(
+            packet_name = packet_name.as_str(), packet_id = packet_id;
+            "Identified '{}' ({}) with {}",
+            packet_name, packet_id, error
+        );
+    }
+
+    // Count the identification.
+    let stats = from_realm!(realm => JsStats);
+    stats.0.set(stats.0.get() + 1);
+
+    // Skip the cache if this field was just identified the same way, and the cache has not changed since.
+    let key = (packet_name.clone(), packet_id, packet_field.field_id);
+    let identified = from_realm!(realm => JsIdentified);
+    if identified.is_current() && identified.fields.borrow().get(&key) == Some(&packet_field) {
+        return Ok(JsValue::Undefined);
+    }
+
+    // Fetch the cache from the realm.
+    // This is done last, so the lock is held only while the cache is updated.
+    let mut cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    // Forget the recorded fields if anything else changed the cache,
+    // such as an undo, a rename, or another script.
+    let mut fields = identified.fields.borrow_mut();
+    if !identified.is_current() {
+        fields.clear();
+    }
+
+    // Update the cache.
+    cache.update(packet_name, packet_id, packet_field.clone());
+
+    // The cache is still locked, so only this update changed it since the check.
+    fields.insert(key, packet_field);
+    identified.recorded.set(identified.generation.load(Ordering::Acquire));
+
+    Ok(JsValue::Undefined)
+}
+
+/// JavaScript-compatible function that identifies multiple fields of a packet at once.
+fn js_identify_all(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    // Get the data from the arguments.
+    let Some(packet_name) = args.first() else {
+        return js_error!("missing packet name argument");
+    };
+    let Some(packet_id) = args.get(1) else {
+        return js_error!("missing packet ID argument");
+    };
+    let Some(fields) = args.get(2) else {
+        return js_error!("missing fields argument");
+    };
 
     // Convert the data into Rust-owned values.
     let packet_name = js_convert!(packet_name, as_string).to_std_string_escaped();
     let packet_id = js_convert!(packet_id, as_number) as u16;
-    let packet_field = MessageField::try_from_js(field, context)?;
+    let fields = JsArray::from_object(js_convert!(fields, as_object).clone())?;
+
+    let strict = {
+        let realm = context.realm().host_defined();
+        from_realm!(realm => JsConfig).0.strict_field_types
+    };
+
+    let mut packet_fields = vec![];
+    for index in 0..fields.length(context)? {
+        let field = fields.get(index, context)?;
+        let mut packet_field = MessageField::try_from_js(&field, context)?;
+        packet_field.confidence = packet_field.confidence.map(|confidence| confidence.clamp(0.0, 1.0));
+
+        // Check the field's type, which would otherwise break proto export.
+        if let Err(error) = packet_field.validate() {
+            if strict {
+                return js_error!(error);
+            }
+
+            warn!(
+                packet_name = packet_name.as_str(), packet_id = packet_id;
+                "Identified '{}' ({}) with {}",
+                packet_name, packet_id, error
+            );
+        }
+
+        packet_fields.push(packet_field);
+    }
+
+    let realm = context.realm().clone();
+    let realm = realm.host_defined_mut();
+
+    // Update the cache with every field under a single lock.
+    let count = packet_fields.len() as u64;
+    {
+        let mut cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        cache.update_many(packet_name, packet_id, packet_fields);
+    }
+
+    // Count the identifications.
+    let stats = from_realm!(realm => JsStats);
+    stats.0.set(stats.0.get() + count);
+
+    Ok(JsValue::Undefined)
+}
+
+/// JavaScript-compatible function that checks if a packet ID is being seen for the first time.
+///
+/// This returns `true` exactly once per ID.
+fn js_first_seen(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    // Fetch the cache from the realm.
+    let realm = context.realm().host_defined_mut();
+    let mut cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let Some(packet_id) = args.first() else {
+        return js_error!("missing packet ID argument");
+    };
+    let packet_id = js_convert!(packet_id, as_number) as u16;
+
+    Ok(JsValue::from(cache.first_seen(packet_id)))
+}
+
+/// JavaScript-compatible function that identifies a packet without any fields.
+fn js_guess_name(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    // Fetch the cache from the realm.
+    let realm = context.realm().host_defined_mut();
+    let mut cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    // Get the data from the arguments.
+    let Some(packet_name) = args.first() else {
+        return js_error!("missing packet name argument");
+    };
+    let Some(packet_id) = args.get(1) else {
+        return js_error!("missing packet ID argument");
+    };
+
+    let packet_name = js_convert!(packet_name, as_string).to_std_string_escaped();
+    let packet_id = js_convert!(packet_id, as_number) as u16;
 
     // Update the cache.
-    cache.update(packet_name, packet_id, packet_field);
+    cache.update_name(packet_name, packet_id);
+
+    // Count the identification.
+    let stats = from_realm!(realm => JsStats);
+    stats.0.set(stats.0.get() + 1);
 
     Ok(JsValue::Undefined)
 }
@@ -461,14 +2788,10 @@ fn js_identify(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult
 fn js_is_known(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
     // Fetch the cache from the realm.
     let realm = context.realm().host_defined_mut();
-    let Ok(cache) = from_realm!(realm => JsCache).0.lock() else {
-        return Err(JsNativeError::typ()
-            .with_message("failed to get cache")
-            .into());
-    };
+    let cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
 
     // Get the data from the arguments.
-    let Some(packet_id) = args.get(0) else {
+    let Some(packet_id) = args.first() else {
         return js_error!("missing packet ID argument");
     };
 
@@ -482,3 +2805,152 @@ fn js_is_known(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult
         js_error!("invalid packet ID type")
     }
 }
+
+/// Creates an object which reads and updates the cache in the context's realm.
+///
+/// Changes made through the object are made to the live cache, not a copy.
+fn cache_handle(context: &mut Context) -> JsObject {
+    ObjectInitializer::new(context)
+        .function(NativeFunction::from_fn_ptr(js_is_known), js_string!("isKnown"), 1)
+        .function(NativeFunction::from_fn_ptr(js_field_known), js_string!("fieldKnown"), 3)
+        .function(NativeFunction::from_fn_ptr(js_first_seen), js_string!("firstSeen"), 1)
+        .function(NativeFunction::from_fn_ptr(js_guess_name), js_string!("guessName"), 2)
+        .function(NativeFunction::from_fn_ptr(js_identify), js_string!("identify"), 3)
+        .function(NativeFunction::from_fn_ptr(js_identify_all), js_string!("identifyAll"), 3)
+        .function(NativeFunction::from_fn_ptr(js_cache_to_json), js_string!("toJSON"), 0)
+        .build()
+}
+
+/// JavaScript-compatible function that copies the current contents of the cache.
+fn js_cache_to_json(_: &JsValue, _: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    // Fetch the cache from the realm.
+    let cache = {
+        let realm = context.realm().host_defined();
+        let cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match serde_json::to_value(&*cache) {
+            Ok(cache) => cache,
+            Err(error) => return js_error!(error.to_string())
+        }
+    };
+
+    JsValue::from_json(&cache, context)
+}
+
+/// JavaScript-compatible function that registers the value names of an enum field.
+///
+/// The mapping may either map values to names (`{ 0: "NONE" }`),
+/// or names to values (`{ NONE: 0 }`).
+fn js_register_enum(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let Some(message_name) = args.first() else {
+        return js_error!("missing message name argument");
+    };
+    let Some(field_id) = args.get(1) else {
+        return js_error!("missing field ID argument");
+    };
+    let Some(mapping) = args.get(2) else {
+        return js_error!("missing mapping argument");
+    };
+
+    let message_name = js_convert!(message_name, as_string).to_std_string_escaped();
+    let field_id = js_convert!(field_id, as_number) as i32;
+
+    let serde_json::Value::Object(mapping) = mapping.to_json(context)? else {
+        return js_error!("mapping must be an object");
+    };
+
+    // Read each entry in whichever direction it was written.
+    let mut values = HashMap::new();
+    for (key, value) in mapping {
+        match (key.parse::<i64>(), value) {
+            (Ok(number), serde_json::Value::String(name)) => values.insert(number, name),
+            (_, serde_json::Value::Number(number)) => match number.as_i64() {
+                Some(number) => values.insert(number, key),
+                None => return js_error!("enum values must be integers")
+            },
+            _ => return js_error!("invalid enum mapping entry")
+        };
+    }
+
+    // Fetch the enum names from the realm.
+    let realm = context.realm().clone();
+    let realm = realm.host_defined_mut();
+    let mut enums = from_realm!(realm => JsEnums).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    enums
+        .entry(message_name)
+        .or_default()
+        .insert(field_id, values);
+
+    Ok(JsValue::Undefined)
+}
+
+/// JavaScript-compatible function that fetches a value from the shared state.
+fn js_shared_get(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let key = args.get_or_undefined(0);
+    let key = key.to_string(context)?.to_std_string_escaped();
+
+    // Fetch the shared state from the realm.
+    let value = {
+        let realm = context.realm().clone();
+        let realm = realm.host_defined_mut();
+        let shared = from_realm!(realm => JsShared).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        shared.get(&key).cloned()
+    };
+
+    match value {
+        Some(value) => JsValue::from_json(&value, context),
+        None => Ok(JsValue::Undefined)
+    }
+}
+
+/// JavaScript-compatible function that stores a value in the shared state.
+///
+/// Setting a value to `undefined` removes it.
+fn js_shared_set(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let key = args.get_or_undefined(0);
+    let key = key.to_string(context)?.to_std_string_escaped();
+
+    let value = args.get_or_undefined(1);
+    let value = match value.is_undefined() {
+        true => None,
+        false => Some(value.to_json(context)?)
+    };
+
+    // Fetch the shared state from the realm.
+    let realm = context.realm().clone();
+    let realm = realm.host_defined_mut();
+    let mut shared = from_realm!(realm => JsShared).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    match value {
+        Some(value) => shared.insert(key, value),
+        None => shared.remove(&key)
+    };
+
+    Ok(JsValue::Undefined)
+}
+
+/// JavaScript-compatible function that checks if a field of a message is known.
+fn js_field_known(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    // Fetch the cache from the realm.
+    let realm = context.realm().host_defined_mut();
+    let cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+    // Get the data from the arguments.
+    let Some(packet_name) = args.first() else {
+        return js_error!("missing packet name argument");
+    };
+    let Some(field_id) = args.get(1) else {
+        return js_error!("missing field ID argument");
+    };
+
+    let packet_name = js_convert!(packet_name, as_string).to_std_string_escaped();
+    let field_id = js_convert!(field_id, as_number) as u16;
+    let field_type = match args.get_or_undefined(2) {
+        JsValue::Undefined => None,
+        field_type => Some(js_convert!(field_type, as_string).to_std_string_escaped())
+    };
+
+    Ok(JsValue::Boolean(cache.field_known(&packet_name, field_id, field_type.as_deref())))
+}
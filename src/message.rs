@@ -1,12 +1,16 @@
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError};
 use anyhow::{Result, anyhow};
 use paste::paste;
-use boa_engine::{js_string, Context, Finalize, JsData, JsResult, JsValue, NativeFunction, Trace, JsNativeError, JsObject};
+use boa_engine::{js_string, Context, JsArgs, JsBigInt, Finalize, JsData, JsResult, JsValue, NativeFunction, Trace, JsNativeError, JsObject};
 use boa_engine::class::{Class, ClassBuilder};
-use boa_engine::object::builtins::JsArray;
+use boa_engine::object::builtins::{JsArray, JsArrayBuffer};
 use boa_engine::value::TryIntoJs;
 use protoshark::{Number, SerializedMessage as ProtoMessage, Value as ProtoValue, VarInt};
-use crate::{js_catch, js_convert, js_error};
+use log::warn;
+use crate::cache::FieldValue;
+use crate::matcher::{JsCache, JsConfig, JsEnums};
+use crate::{from_realm, js_catch, js_convert, js_error, wire};
 
 /// Generates JavaScript-compatible methods for transforming
 /// `protoshark`'s `Value`s into JavaScript values.
@@ -24,20 +28,21 @@ macro_rules! js_method {
                         return js_error!("failed to cast object to SerializedMessage");
                     };
 
-                    let Some(field_id) = args.get(0) else {
+                    let Some(field_id) = args.first() else {
                         return js_error!("missing field ID");
                     };
                     let field_id = js_convert!(field_id, as_number) as i32;
 
+                    // If the field is missing or has a different type, return the default.
                     let value = message.get(field_id);
                     match value {
                         Some(value) => {
                             match value {
                                 crate::message::Value::$value_type(_) => value.try_into_js(context),
-                                _ => Ok(JsValue::Undefined)
+                                _ => Ok(args.get_or_undefined(1).clone())
                             }
                         },
-                        None => Ok(JsValue::Undefined)
+                        None => Ok(args.get_or_undefined(1).clone())
                     }
                 }
 
@@ -53,7 +58,7 @@ macro_rules! js_method {
 
                     // Get all fields.
                     let fields = JsArray::new(context);
-                    for (field_id, value) in &message.inner {
+                    for (field_id, value) in message.inner.iter() {
                         if let Value::$value_type(_) = value {
                             let field_id = (*field_id).try_into_js(context)?;
                             let field_value = value.try_into_js(context)?;
@@ -73,6 +78,75 @@ macro_rules! js_method {
     };
 }
 
+/// Generates JavaScript-compatible methods for reading
+/// values using a specific interpretation of their `Value`.
+///
+/// The conversion method should return `undefined` if the value cannot be interpreted.
+macro_rules! js_interpret {
+    ($($name:ident => $convert:ident),*) => {
+        $(
+            paste! {
+                pub(crate) fn [<js_get_ $name:lower>](
+                    this: &JsValue,
+                    args: &[JsValue],
+                    context: &mut Context
+                ) -> JsResult<JsValue> {
+                    let object = js_convert!(this, as_object);
+                    let Some(message) = object.downcast_ref::<crate::message::SerializedMessage>() else {
+                        return js_error!("failed to cast object to SerializedMessage");
+                    };
+
+                    let Some(field_id) = args.first() else {
+                        return js_error!("missing field ID");
+                    };
+                    let field_id = js_convert!(field_id, as_number) as i32;
+
+                    // If the field is missing or cannot be interpreted, return the default.
+                    let value = match message.get(field_id) {
+                        Some(value) => value.$convert(context)?,
+                        None => JsValue::Undefined
+                    };
+
+                    match value.is_undefined() {
+                        true => Ok(args.get_or_undefined(1).clone()),
+                        false => Ok(value)
+                    }
+                }
+
+                pub(crate) fn [<js_get_all_ $name:lower>](
+                    this: &JsValue,
+                    _: &[JsValue],
+                    context: &mut Context
+                ) -> JsResult<JsValue> {
+                    let object = js_convert!(this, as_object);
+                    let Some(message) = object.downcast_ref::<crate::message::SerializedMessage>() else {
+                        return js_error!("failed to cast object to SerializedMessage");
+                    };
+
+                    // Get all fields which can be interpreted.
+                    let fields = JsArray::new(context);
+                    for (field_id, value) in message.inner.iter() {
+                        let field_value = value.$convert(context)?;
+                        if field_value.is_undefined() {
+                            continue;
+                        }
+
+                        let field_id = (*field_id).try_into_js(context)?;
+
+                        let object = JsArray::new(context);
+                        object.push(field_id, context)?;
+                        object.push(field_value, context)?;
+
+                        fields.push(object, context)?;
+                    }
+
+                    Ok(fields.into())
+                }
+            }
+        )*
+    };
+}
+
 /// Generates the code to declare the JavaScript prototype methods.
 macro_rules! js_impl {
     ($class:expr => $($value_type:ident),*) => {
@@ -92,41 +166,239 @@ macro_rules! js_impl {
     };
 }
 
+/// A decoded `protoshark` message, alongside the bytes it was decoded from.
+pub struct DecodedMessage {
+    /// The decoded message.
+    pub message: ProtoMessage,
+
+    /// The encoded message, if it is available.
+    pub raw: Option<Vec<u8>>,
+
+    /// The message converted for scripts.
+    ///
+    /// This is converted once, and shared by every comparer the packet is given to.
+    converted: OnceLock<SerializedMessage>
+}
+
+impl DecodedMessage {
+    /// Creates a decoded message, alongside the bytes it was decoded from.
+    pub fn new(message: ProtoMessage, raw: Option<Vec<u8>>) -> Self {
+        DecodedMessage {
+            message,
+            raw,
+            converted: OnceLock::new()
+        }
+    }
+
+    /// Converts the message, limiting how deeply nested messages are converted.
+    ///
+    /// The message is only converted once, so later calls return the first conversion.
+    pub(crate) fn converted(&self, max_depth: usize, packet_id: u16) -> &SerializedMessage {
+        self.converted.get_or_init(|| {
+            let (converted, truncated) = SerializedMessage::from_with_depth(
+                &self.message, self.raw.as_deref(), max_depth
+            );
+            if truncated {
+                warn!(
+                    packet_id = packet_id;
+                    "Packet {} was nested deeper than {} levels, so deeper messages were left as bytes",
+                    packet_id, max_depth
+                );
+            }
+
+            converted
+        })
+    }
+}
+
 /// A protobuf-encoded message.
-#[derive(Debug, JsData, Trace, Finalize, TryIntoJs)]
+#[derive(Debug, Clone, JsData, Trace, Finalize)]
 pub struct SerializedMessage {
     #[unsafe_ignore_trace]
-    inner: HashMap<i32, Value>
+    inner: Arc<HashMap<i32, Value>>,
+
+    /// Every value of each field which occurs more than once, in order.
+    ///
+    /// `inner` holds the last of these values.
+    #[unsafe_ignore_trace]
+    repeated: Arc<HashMap<i32, Vec<Value>>>,
+
+    /// The encoded bytes of each field.
+    ///
+    /// This is empty if the encoded message is not available.
+    #[unsafe_ignore_trace]
+    raw: Arc<HashMap<i32, Vec<u8>>>,
+
+    /// The number of times each field occurs in the encoded message.
+    ///
+    /// This is empty if the encoded message is not available.
+    #[unsafe_ignore_trace]
+    counts: Arc<HashMap<i32, usize>>,
+
+    /// The ID of the packet this message was received in.
+    ///
+    /// This is only set for the header and data passed to `compare`.
+    #[unsafe_ignore_trace]
+    packet_id: Option<u16>
 }
 
 impl SerializedMessage {
-    /// Converts a `protoshark` message into a JavaScript-convertable message.
-    pub fn from(message: &ProtoMessage) -> Self {
+    /// Converts a `protoshark` message, limiting how deeply nested messages are converted.
+    ///
+    /// If the encoded message is provided, the encoded bytes of each field are kept.
+    ///
+    /// Messages nested deeper than the limit are kept as `Bytes` of their encoded contents,
+    /// which are empty if the encoded message is not available.
+    ///
+    /// Returns the message, and whether any nested messages were truncated.
+    pub fn from_with_depth(message: &ProtoMessage, raw: Option<&[u8]>, max_depth: usize) -> (Self, bool) {
+        let mut truncated = false;
+        let message = Self::convert(message, raw, max_depth, &mut truncated);
+
+        (message, truncated)
+    }
+
+    /// Recursively converts a `protoshark` message.
+    ///
+    /// `depth` is the number of levels of nested messages which may still be converted.
+    fn convert(message: &ProtoMessage, raw: Option<&[u8]>, depth: usize, truncated: &mut bool) -> Self {
         let mut map = HashMap::new();
+
+        // Split the encoded message into its fields.
+        let fields = raw
+            .and_then(wire::split_fields)
+            .unwrap_or_default();
         
         // Convert every value in the map.
-        for entry in message {
-            let (key, value) = entry;
-            let value = match value {
-                ProtoValue::VarInt(value) => Value::VarInt(value.clone()),
-                ProtoValue::Float(value) => Value::Float(value.clone()),
-                ProtoValue::Double(value) => Value::Double(value.clone()),
-                ProtoValue::String(value) => Value::String(value.clone()),
-                ProtoValue::Bytes(value) => Value::Bytes(value.clone()),
-                ProtoValue::Message(value) => Value::Message(SerializedMessage::from(value))
+        let mut repeated = HashMap::new();
+        for (key, value) in message {
+            let key = *key as i32;
+            let field = fields.get(&key);
+
+            let values = Self::occurrences(value);
+            let count = values.len();
+
+            let mut converted = Vec::with_capacity(count);
+            for (index, value) in values.into_iter().enumerate() {
+                // The encoded bytes are only known if every occurrence was accounted for.
+                let raw = field
+                    .filter(|field| field.values.len() == count)
+                    .map(|field| field.values[index]);
+
+                let Some(value) = Self::convert_value(value, raw, depth, truncated) else {
+                    continue;
+                };
+                converted.push(value);
+            }
+
+            let Some(last) = converted.last().cloned() else {
+                continue;
             };
-            
-            map.insert(*key, value);
+            if converted.len() > 1 {
+                repeated.insert(key, converted);
+            }
+
+            map.insert(key, last);
         }
+
+        // Count the occurrences of each field.
+        let counts = fields
+            .iter()
+            .map(|(key, field)| (*key, field.count))
+            .collect();
+
+        let raw = fields
+            .into_iter()
+            .map(|(key, field)| (key, field.data.to_vec()))
+            .collect();
         
-        SerializedMessage { inner: map }
+        SerializedMessage {
+            inner: Arc::new(map),
+            repeated: Arc::new(repeated),
+            raw: Arc::new(raw),
+            counts: Arc::new(counts),
+            packet_id: None
+        }
+    }
+
+    /// Splits a `protoshark` value into the value of each occurrence of its field.
+    ///
+    /// A length-delimited field which is valid UTF-8 and also decodes as a message
+    /// is reported as a string followed by a message. The string is kept,
+    /// and the message can be read with `asMessage`.
+    fn occurrences(value: &ProtoValue) -> Vec<&ProtoValue> {
+        let ProtoValue::Repeated(values) = value else {
+            return vec![value];
+        };
+
+        let mut occurrences = Vec::with_capacity(values.len());
+        let mut values = values.iter().peekable();
+        while let Some(value) = values.next() {
+            if let ProtoValue::String(_) = value {
+                values.next_if(|next| matches!(next, ProtoValue::Message(_)));
+            }
+
+            occurrences.push(value);
+        }
+
+        occurrences
+    }
+
+    /// Converts a single `protoshark` value.
+    ///
+    /// Returns `None` for nested repeated values, which `protoshark` never produces.
+    fn convert_value(value: &ProtoValue, raw: Option<&[u8]>, depth: usize, truncated: &mut bool) -> Option<Value> {
+        let value = match value {
+            ProtoValue::VarInt(value) => Value::VarInt(value.clone()),
+            ProtoValue::Float(value) => Value::Float(*value),
+            ProtoValue::Double(value) => Value::Double(*value),
+            ProtoValue::String(value) => Value::String(value.clone()),
+            ProtoValue::Bytes(value) => Value::Bytes(value.clone()),
+            ProtoValue::Message(value) => {
+                if depth == 0 {
+                    *truncated = true;
+                    Value::Bytes(raw.map(<[u8]>::to_vec).unwrap_or_default())
+                } else {
+                    Value::Message(Self::convert(value, raw, depth - 1, truncated))
+                }
+            },
+            ProtoValue::Repeated(_) => return None
+        };
+
+        Some(value)
+    }
+
+    /// Fetches the maximum message depth from the configuration in the context's realm.
+    pub(crate) fn max_depth(context: &Context) -> usize {
+        context.realm()
+            .host_defined()
+            .get::<JsConfig>()
+            .and_then(|config| config.0.max_message_depth)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Converts a message decoded by a script, applying the configured depth limit.
+    pub(crate) fn from_script(context: &Context, message: &ProtoMessage, raw: &[u8]) -> Self {
+        let max_depth = Self::max_depth(context);
+        let (message, truncated) = Self::from_with_depth(message, Some(raw), max_depth);
+        if truncated {
+            warn!(
+                "Decoded message was nested deeper than {} levels, so deeper messages were left as bytes",
+                max_depth
+            );
+        }
+
+        message
     }
 
-    /// Converts a `protoshark` message into a JavaScript object.
+    /// Converts a decoded packet into a JavaScript object.
     ///
-    /// Under the hood, this uses `SerializedMessage::from`.
-    pub fn from_to_js(context: &mut Context, message: &ProtoMessage) -> Result<JsObject> {
-        let message = Self::from(message);
+    /// The packet is only converted the first time it is given to a script,
+    /// and the converted fields are shared with every other script.
+    pub fn from_to_js(context: &mut Context, message: &DecodedMessage, packet_id: u16) -> Result<JsObject> {
+        let mut message = message.converted(Self::max_depth(context), packet_id).clone();
+        message.packet_id = Some(packet_id);
+
         Ok(js_catch!(Self::from_data(message, context)))
     }
 
@@ -137,6 +409,93 @@ impl SerializedMessage {
         self.inner.get(&key)
     }
 
+    /// Fetches every value of a field, in the order they occur.
+    ///
+    /// Returns an empty slice if the field with the given ID does not exist.
+    pub fn get_all(&self, key: i32) -> &[Value] {
+        match self.repeated.get(&key) {
+            Some(values) => values,
+            None => self.inner.get(&key).map(std::slice::from_ref).unwrap_or_default()
+        }
+    }
+
+    /// Checks if two messages have the same fields and values, including nested messages.
+    ///
+    /// Floating-point values are compared by their bits, so `NaN` equals itself.
+    pub fn structurally_equals(&self, other: &SerializedMessage) -> bool {
+        self.inner.len() == other.inner.len() &&
+            self.inner.keys().all(|key| {
+                let (values, others) = (self.get_all(*key), other.get_all(*key));
+                values.len() == others.len() &&
+                    values.iter().zip(others).all(|(value, other)| value.structurally_equals(other))
+            })
+    }
+
+    /// Converts the message into a JSON object, keyed by field ID.
+    ///
+    /// Bytes are represented as arrays of numbers.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut keys = self.inner.keys().copied().collect::<Vec<_>>();
+        keys.sort();
+
+        let fields = keys
+            .into_iter()
+            .map(|key| {
+                let value = match &self.inner[&key] {
+                    Value::VarInt(value) => match Number::closest(value.clone()) {
+                        Number::Integer(value) => value.into(),
+                        Number::UnsignedInteger(value) => value.into(),
+                        Number::Long(value) => value.into(),
+                        Number::UnsignedLong(value) => value.into()
+                    },
+                    Value::Float(value) => (*value).into(),
+                    Value::Double(value) => (*value).into(),
+                    Value::String(value) => value.clone().into(),
+                    Value::Bytes(value) => value.clone().into(),
+                    Value::Message(value) => value.to_json()
+                };
+
+                (key.to_string(), value)
+            })
+            .collect();
+
+        serde_json::Value::Object(fields)
+    }
+
+    /// A JavaScript-friendly implementation of `SerializedMessage::to_json`.
+    ///
+    /// This is called by `JSON.stringify`.
+    pub(crate) fn js_to_json(this: &JsValue, _: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let json = message.to_json();
+        JsValue::from_json(&json, context)
+    }
+
+    /// Converts the message's values into values which can be stored in the cache.
+    pub(crate) fn to_fields(&self) -> HashMap<i32, FieldValue> {
+        self.inner
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::VarInt(value) => FieldValue::VarInt(raw_varint(value)),
+                    Value::Float(value) => FieldValue::Float(*value),
+                    Value::Double(value) => FieldValue::Double(*value),
+                    Value::String(value) => FieldValue::String(value.clone()),
+                    Value::Bytes(value) => FieldValue::Bytes(value.clone()),
+                    Value::Message(value) => FieldValue::Message(value.to_fields())
+                };
+
+                (*key, value)
+            })
+            .collect()
+    }
+
     /// A JavaScript-friendly implementation of `SerializedMessage::get`.
     ///
     /// # Example (JavaScript)
@@ -152,6 +511,12 @@ impl SerializedMessage {
     ///    info("Field exists.");
     /// }
     /// ```
+    ///
+    /// A default value can be provided, which is returned if the field does not exist.
+    ///
+    /// ```js
+    /// const value = message.get(1, 0);
+    /// ```
     pub(crate) fn js_get(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
         // Get the object.
         let object = js_convert!(this, as_object);
@@ -161,13 +526,58 @@ impl SerializedMessage {
         };
 
         // Invoke the message's `get` method.
-        let Some(field_id) = args.get(0) else {
+        let Some(field_id) = args.first() else {
             return js_error!("missing field ID");
         };
         let field_id = js_convert!(field_id, as_number) as i32;
 
         let value = message.get(field_id);
         match value {
+            Some(value) => value.try_into_js(context),
+            None => Ok(args.get_or_undefined(1).clone())
+        }
+    }
+
+    /// A JavaScript-friendly method to fetch a value by the name of an identified field.
+    ///
+    /// Returns `undefined` if the field has not been identified or does not exist.
+    ///
+    /// # Example (JavaScript)
+    ///
+    /// ```js
+    /// const username = data.getByName("PlayerLoginReq", "username");
+    /// ```
+    pub(crate) fn js_get_by_name(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(message_name) = args.first() else {
+            return js_error!("missing message name");
+        };
+        let Some(field_name) = args.get(1) else {
+            return js_error!("missing field name");
+        };
+        let message_name = js_convert!(message_name, as_string).to_std_string_escaped();
+        let field_name = js_convert!(field_name, as_string).to_std_string_escaped();
+
+        // Look up the field ID in the cache.
+        let field_id = {
+            let realm = context.realm().clone();
+            let realm = realm.host_defined();
+            let cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+            cache.field_id(&message_name, &field_name)
+        };
+
+        let Some(field_id) = field_id else {
+            return Ok(JsValue::Undefined);
+        };
+
+        match message.get(field_id as i32) {
             Some(value) => value.try_into_js(context),
             None => Ok(JsValue::Undefined)
         }
@@ -190,7 +600,393 @@ impl SerializedMessage {
         Ok(array.into())
     }
 
+    /// A JavaScript-friendly method to enumerate over all fields of the message.
+    ///
+    /// Each entry is a `[fieldId, value]` pair.
+    pub(crate) fn js_entries(this: &JsValue, _: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let entries = JsArray::new(context);
+        for (field_id, value) in message.inner.iter() {
+            let field_id = (*field_id).try_into_js(context)?;
+            let field_value = value.try_into_js(context)?;
+
+            let entry = JsArray::new(context);
+            entry.push(field_id, context)?;
+            entry.push(field_value, context)?;
+
+            entries.push(entry, context)?;
+        }
+
+        Ok(entries.into())
+    }
+
+    /// A JavaScript-friendly method to find the first field matching a predicate.
+    ///
+    /// The predicate is called with the field ID and value of each field, in order of field ID.
+    ///
+    /// Returns the first `[fieldId, value]` pair the predicate returns a truthy value for,
+    /// or `undefined` if none match.
+    pub(crate) fn js_find(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        let Some(predicate) = args.get_or_undefined(0).as_callable().cloned() else {
+            return js_error!("predicate must be a function");
+        };
+
+        // Copy the fields, since the predicate may access the message.
+        let mut fields = {
+            let object = js_convert!(this, as_object);
+            let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+                return js_error!("failed to cast object to SerializedMessage");
+            };
+
+            message.inner.iter()
+                .map(|(field_id, value)| (*field_id, value.clone()))
+                .collect::<Vec<_>>()
+        };
+        fields.sort_by_key(|(field_id, _)| *field_id);
+
+        for (field_id, value) in fields {
+            let field_id = field_id.try_into_js(context)?;
+            let field_value = value.try_into_js(context)?;
+
+            let result = predicate.call(
+                &JsValue::undefined(),
+                &[field_id.clone(), field_value.clone()],
+                context
+            )?;
+
+            if result.to_boolean() {
+                let entry = JsArray::new(context);
+                entry.push(field_id, context)?;
+                entry.push(field_value, context)?;
+
+                return Ok(entry.into());
+            }
+        }
+
+        Ok(JsValue::Undefined)
+    }
+
+    /// A JavaScript-friendly method to transform the fields of the message.
+    ///
+    /// The callback is called with the field ID and value of each field, in order of field ID.
+    ///
+    /// Returns an array of the callback's results, skipping `undefined` results.
+    pub(crate) fn js_map(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        let Some(callback) = args.get_or_undefined(0).as_callable().cloned() else {
+            return js_error!("callback must be a function");
+        };
+
+        // Copy the fields, since the callback may access the message.
+        let mut fields = {
+            let object = js_convert!(this, as_object);
+            let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+                return js_error!("failed to cast object to SerializedMessage");
+            };
+
+            message.inner.iter()
+                .map(|(field_id, value)| (*field_id, value.clone()))
+                .collect::<Vec<_>>()
+        };
+        fields.sort_by_key(|(field_id, _)| *field_id);
+
+        let results = JsArray::new(context);
+        for (field_id, value) in fields {
+            let field_id = field_id.try_into_js(context)?;
+            let field_value = value.try_into_js(context)?;
+
+            let result = callback.call(
+                &JsValue::undefined(),
+                &[field_id, field_value],
+                context
+            )?;
+
+            if !result.is_undefined() {
+                results.push(result, context)?;
+            }
+        }
+
+        Ok(results.into())
+    }
+
+    /// A JavaScript-friendly method to fetch the encoded bytes of a field.
+    ///
+    /// For length-delimited fields, this excludes the length prefix.
+    ///
+    /// Returns `undefined` if the field does not exist or the encoded message is not available.
+    pub(crate) fn js_raw(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        match message.raw.get(&field_id) {
+            Some(bytes) => {
+                let buffer = JsArrayBuffer::from_byte_block(bytes.clone(), context)?;
+                Ok(buffer.into())
+            },
+            None => Ok(JsValue::Undefined)
+        }
+    }
+
+    /// A JavaScript-friendly method to count the occurrences of a field.
+    ///
+    /// Repeated fields are counted from the encoded message.
+    /// If the encoded message is not available, the decoded values are counted instead.
+    ///
+    /// Returns `0` if the field does not exist.
+    pub(crate) fn js_count(this: &JsValue, args: &[JsValue], _: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        let count = match message.counts.get(&field_id) {
+            Some(count) => *count,
+            None => message.get_all(field_id).len()
+        };
+
+        Ok(JsValue::from(count as u32))
+    }
+
+    /// A JavaScript-friendly method to fetch every value of a field, in the order they occur.
+    ///
+    /// Returns an empty array if the field does not exist.
+    pub(crate) fn js_values(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        let values = JsArray::new(context);
+        for value in message.get_all(field_id) {
+            values.push(value.try_into_js(context)?, context)?;
+        }
+
+        Ok(values.into())
+    }
+
+    /// A JavaScript-friendly method to fetch the wire type of a field.
+    ///
+    /// Returns `undefined` if the field does not exist.
+    pub(crate) fn js_wire_type(this: &JsValue, args: &[JsValue], _: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        match message.get(field_id) {
+            Some(value) => Ok(JsValue::from(value.wire_type())),
+            None => Ok(JsValue::Undefined)
+        }
+    }
+
+    /// A JavaScript-friendly method to fetch a range of bytes from a length-delimited field.
+    ///
+    /// Like `Array.prototype.slice`, negative indices count from the end,
+    /// and the range is clamped to the field's length.
+    /// If `end` is omitted, the range continues to the end of the field.
+    ///
+    /// Fields decoded as strings or messages are sliced from their encoded bytes.
+    ///
+    /// Returns `undefined` if the field does not exist or is not length-delimited.
+    pub(crate) fn js_slice(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        let bytes = match message.get(field_id) {
+            Some(Value::Bytes(bytes)) => bytes.as_slice(),
+            Some(Value::String(_) | Value::Message(_)) => match message.raw.get(&field_id) {
+                Some(bytes) => bytes.as_slice(),
+                None => return Ok(JsValue::Undefined)
+            },
+            _ => return Ok(JsValue::Undefined)
+        };
+
+        let start = args.get_or_undefined(1).as_number();
+        let end = args.get_or_undefined(2).as_number();
+        let range = &bytes[slice_range(bytes.len(), start, end)];
+
+        let buffer = JsArrayBuffer::from_byte_block(range.to_vec(), context)?;
+        Ok(buffer.into())
+    }
+
+    /// A JavaScript-friendly method to deeply compare two messages.
+    ///
+    /// Returns `false` if the other value is not a message.
+    pub(crate) fn js_equals(this: &JsValue, args: &[JsValue], _: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(other) = args.get_or_undefined(0).as_object() else {
+            return Ok(JsValue::from(false));
+        };
+        let Some(other) = other.downcast_ref::<SerializedMessage>() else {
+            return Ok(JsValue::from(false));
+        };
+
+        Ok(JsValue::from(message.structurally_equals(&other)))
+    }
+
+    /// A JavaScript-friendly method to decode a `Bytes` or `String` field as a nested message.
+    ///
+    /// A field which is valid UTF-8 is read as a string, even if it also decodes as a message,
+    /// so this is how such fields are read as messages.
+    /// If the field is already a message, it is returned as-is.
+    ///
+    /// Returns `undefined` if the field does not exist or cannot be decoded.
+    pub(crate) fn js_as_message(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        let bytes = match message.get(field_id) {
+            Some(Value::Bytes(bytes)) => bytes.as_slice(),
+            Some(Value::String(string)) => string.as_bytes(),
+            Some(Value::Message(message)) => return Ok(Self::from_data(message.clone(), context)?.into()),
+            _ => return Ok(JsValue::Undefined)
+        };
+
+        let Ok(decoded) = protoshark::decode(bytes) else {
+            return Ok(JsValue::Undefined);
+        };
+
+        let message = Self::from_script(context, &decoded, bytes);
+        Ok(Self::from_data(message, context)?.into())
+    }
+
+    /// A JavaScript-friendly method to fetch a `VarInt` field as an enum value name.
+    ///
+    /// Enum names are registered by scripts with `registerEnum`.
+    /// If the message name is omitted, the name of the packet is used.
+    ///
+    /// Returns the number if the value is not mapped,
+    /// or `undefined` if the field does not exist or is not a `VarInt`.
+    ///
+    /// # Example (JavaScript)
+    ///
+    /// ```js
+    /// registerEnum("PlayerLoginRsp", 2, { 0: "SUCCESS", 1: "BANNED" });
+    /// info(data.enum(2)); // "BANNED"
+    /// ```
+    pub(crate) fn js_enum(this: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+        // Get the object.
+        let object = js_convert!(this, as_object);
+        // Cast into a `SerializedMessage`.
+        let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+            return js_error!("failed to cast object to SerializedMessage");
+        };
+
+        let Some(field_id) = args.first() else {
+            return js_error!("missing field ID");
+        };
+        let field_id = js_convert!(field_id, as_number) as i32;
+
+        let Some(Value::VarInt(value)) = message.get(field_id) else {
+            return Ok(JsValue::Undefined);
+        };
+        let value = raw_varint(value) as i64;
+
+        let message_name = match args.get(1) {
+            Some(name) if !name.is_undefined() => {
+                Some(js_convert!(name, as_string).to_std_string_escaped())
+            },
+            _ => None
+        };
+
+        // Look up the value's name.
+        let name = {
+            let realm = context.realm().clone();
+            let realm = realm.host_defined();
+
+            let message_name = match message_name {
+                Some(name) => Some(name),
+                None => {
+                    let cache = from_realm!(realm => JsCache).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+                    message.packet_id
+                        .and_then(|id| cache.name_of(id))
+                        .map(|name| name.to_string())
+                }
+            };
+
+            let enums = from_realm!(realm => JsEnums).0.lock().unwrap_or_else(PoisonError::into_inner);
+
+            message_name.and_then(|name| enums
+                .get(&name)
+                .and_then(|fields| fields.get(&field_id))
+                .and_then(|values| values.get(&value))
+                .cloned())
+        };
+
+        match name {
+            Some(name) => Ok(js_string!(name).into()),
+            None => value.try_into_js(context)
+        }
+    }
+
     js_method!(VarInt, Float, Double, String, Bytes, Message);
+
+    js_interpret!(
+        Sint => to_sint, Bool => to_bool,
+        Uint => to_uint, Int => to_int, Long => to_long, Bigint => to_bigint,
+        Fixed32 => to_fixed32, Sfixed32 => to_sfixed32,
+        Fixed64 => to_fixed64, Sfixed64 => to_sfixed64
+    );
 }
 
 impl Class for SerializedMessage {
@@ -205,12 +1001,78 @@ impl Class for SerializedMessage {
             NativeFunction::from_fn_ptr(Self::js_get)
         );
 
+        class.method(
+            js_string!("getByName"), 2,
+            NativeFunction::from_fn_ptr(Self::js_get_by_name)
+        );
+
+        class.method(
+            js_string!("toJSON"), 0,
+            NativeFunction::from_fn_ptr(Self::js_to_json)
+        );
+
         class.method(
             js_string!("keys"), 0,
             NativeFunction::from_fn_ptr(Self::js_keys)
         );
 
+        class.method(
+            js_string!("entries"), 0,
+            NativeFunction::from_fn_ptr(Self::js_entries)
+        );
+
+        class.method(
+            js_string!("find"), 1,
+            NativeFunction::from_fn_ptr(Self::js_find)
+        );
+
+        class.method(
+            js_string!("map"), 1,
+            NativeFunction::from_fn_ptr(Self::js_map)
+        );
+
+        class.method(
+            js_string!("raw"), 1,
+            NativeFunction::from_fn_ptr(Self::js_raw)
+        );
+
+        class.method(
+            js_string!("count"), 1,
+            NativeFunction::from_fn_ptr(Self::js_count)
+        );
+
+        class.method(
+            js_string!("values"), 1,
+            NativeFunction::from_fn_ptr(Self::js_values)
+        );
+
+        class.method(
+            js_string!("wireType"), 1,
+            NativeFunction::from_fn_ptr(Self::js_wire_type)
+        );
+
+        class.method(
+            js_string!("slice"), 3,
+            NativeFunction::from_fn_ptr(Self::js_slice)
+        );
+
+        class.method(
+            js_string!("equals"), 1,
+            NativeFunction::from_fn_ptr(Self::js_equals)
+        );
+
+        class.method(
+            js_string!("asMessage"), 1,
+            NativeFunction::from_fn_ptr(Self::js_as_message)
+        );
+
+        class.method(
+            js_string!("enum"), 1,
+            NativeFunction::from_fn_ptr(Self::js_enum)
+        );
+
         js_impl!(class => VarInt, Float, Double, String, Bytes, Message);
+        js_impl!(class => Sint, Bool, Uint, Int, Long, Bigint, Fixed32, Sfixed32, Fixed64, Sfixed64);
 
         Ok(())
     }
@@ -220,12 +1082,44 @@ impl Class for SerializedMessage {
     /// 
     /// If it is called anyway, we return an empty message.
     fn data_constructor(_: &JsValue, _: &[JsValue], _: &mut Context) -> JsResult<Self> {
-        Ok(SerializedMessage { inner: HashMap::new() })
+        Ok(SerializedMessage {
+            inner: Arc::default(),
+            repeated: Arc::default(),
+            raw: Arc::default(),
+            counts: Arc::default(),
+            packet_id: None
+        })
+    }
+}
+
+/// Nested messages are converted into plain objects with an `inner` property holding the fields,
+/// unless `Config::nested_message_methods` is set.
+impl TryIntoJs for SerializedMessage {
+    fn try_into_js(&self, context: &mut Context) -> JsResult<JsValue> {
+        let methods = context.realm()
+            .host_defined()
+            .get::<JsConfig>()
+            .is_some_and(|config| config.0.nested_message_methods);
+
+        if methods {
+            let object = Self::from_data(self.clone(), context)?;
+            return Ok(object.into());
+        }
+
+        let inner = self.inner.as_ref().try_into_js(context)?;
+        let object = JsObject::with_object_proto(context.intrinsics());
+        object.create_data_property_or_throw(js_string!("inner"), inner, context)?;
+
+        Ok(object.into())
     }
 }
 
 /// Represents one (or multiple) values in a protobuf-encoded message.
-#[derive(Debug)]
+///
+/// There is no variant for legacy groups (wire types `3` and `4`),
+/// since `protoshark` rejects any message which contains them.
+/// Such packets fail to decode with a `BiscuitError::Decode` naming the group field.
+#[derive(Debug, Clone)]
 pub enum Value {
     VarInt(VarInt),
     Float(f32),
@@ -235,6 +1129,182 @@ pub enum Value {
     Message(SerializedMessage)
 }
 
+impl Value {
+    /// Checks if two values have the same type and contents, including nested messages.
+    ///
+    /// Floating-point values are compared by their bits, so `NaN` equals itself.
+    pub fn structurally_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::VarInt(value), Value::VarInt(other)) => raw_varint(value) == raw_varint(other),
+            (Value::Float(value), Value::Float(other)) => value.to_bits() == other.to_bits(),
+            (Value::Double(value), Value::Double(other)) => value.to_bits() == other.to_bits(),
+            (Value::String(value), Value::String(other)) => value == other,
+            (Value::Bytes(value), Value::Bytes(other)) => value == other,
+            (Value::Message(value), Value::Message(other)) => value.structurally_equals(other),
+            _ => false
+        }
+    }
+
+    /// Returns the protobuf wire type used to encode this value.
+    ///
+    /// This is `0` for varints, `1` for 64-bit values,
+    /// `2` for length-delimited values, and `5` for 32-bit values.
+    pub fn wire_type(&self) -> u8 {
+        match self {
+            Value::VarInt(_) => 0,
+            Value::Double(_) => 1,
+            Value::String(_) | Value::Bytes(_) | Value::Message(_) => 2,
+            Value::Float(_) => 5
+        }
+    }
+
+    /// Interprets a `VarInt` as a zigzag-encoded signed integer.
+    ///
+    /// This is the encoding used by `sint32` and `sint64` fields.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt`.
+    pub fn to_sint(&self, context: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        let value = raw_varint(value);
+        let decoded = ((value >> 1) as i64) ^ -((value & 1) as i64);
+
+        decoded.try_into_js(context)
+    }
+
+    /// Interprets a `VarInt` as an unsigned 32-bit integer.
+    ///
+    /// This is the encoding used by `uint32` fields.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt`.
+    pub fn to_uint(&self, context: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        (raw_varint(value) as u32).try_into_js(context)
+    }
+
+    /// Interprets a `VarInt` as a signed 32-bit integer.
+    ///
+    /// This is the encoding used by `int32` and `enum` fields.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt`.
+    pub fn to_int(&self, context: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        (raw_varint(value) as i32).try_into_js(context)
+    }
+
+    /// Interprets a `VarInt` as a signed 64-bit integer.
+    ///
+    /// This is the encoding used by `int64` fields.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt`.
+    pub fn to_long(&self, context: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        (raw_varint(value) as i64).try_into_js(context)
+    }
+
+    /// Converts a `VarInt` into a `BigInt`, without any loss of precision.
+    ///
+    /// The value is treated as unsigned, which is the encoding used by `uint64` fields.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt`.
+    pub fn to_bigint(&self, _: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        Ok(JsValue::BigInt(JsBigInt::from(raw_varint(value))))
+    }
+
+    /// Interprets a `VarInt` as a boolean.
+    ///
+    /// Returns `undefined` if the value is not a `VarInt` of `0` or `1`.
+    pub fn to_bool(&self, _: &mut Context) -> JsResult<JsValue> {
+        let Value::VarInt(value) = self else {
+            return Ok(JsValue::Undefined);
+        };
+
+        match raw_varint(value) {
+            0 => Ok(JsValue::Boolean(false)),
+            1 => Ok(JsValue::Boolean(true)),
+            _ => Ok(JsValue::Undefined)
+        }
+    }
+}
+
+/// Generates methods which reinterpret the bits of a fixed-width value.
+///
+/// `protoshark` decodes 32-bit fields as `Float` and 64-bit fields as `Double`,
+/// so fixed-width integers are recovered from the bits of those values.
+macro_rules! fixed_interpret {
+    ($($name:ident: $variant:ident => $target:ty),*) => {
+        impl Value {
+            $(
+                #[doc = concat!("Interprets a `", stringify!($variant), "` as a `", stringify!($target), "`.")]
+                ///
+                /// Returns `undefined` if the value has a different wire type.
+                pub fn $name(&self, context: &mut Context) -> JsResult<JsValue> {
+                    let Value::$variant(value) = self else {
+                        return Ok(JsValue::Undefined);
+                    };
+
+                    (value.to_bits() as $target).try_into_js(context)
+                }
+            )*
+        }
+    };
+}
+
+fixed_interpret!(
+    to_fixed32: Float => u32,
+    to_sfixed32: Float => i32,
+    to_fixed64: Double => u64,
+    to_sfixed64: Double => i64
+);
+
+/// Resolves the bounds passed to `slice` into a range of a field with the given length.
+///
+/// Like `Array.prototype.slice`, negative indices count from the end, `NaN` is treated as `0`,
+/// and the range is clamped to the length. A missing `end` continues to the end of the field.
+fn slice_range(length: usize, start: Option<f64>, end: Option<f64>) -> std::ops::Range<usize> {
+    let bound = |index: f64| -> usize {
+        let index = match index.is_nan() {
+            true => 0.0,
+            false => index.trunc()
+        };
+
+        match index < 0.0 {
+            true => (length as f64 + index).max(0.0) as usize,
+            false => index.min(length as f64) as usize
+        }
+    };
+
+    let start = bound(start.unwrap_or(0.0));
+    let end = bound(end.unwrap_or(length as f64));
+
+    start..end.max(start)
+}
+
+/// Reads the raw, unsigned value of a `VarInt`.
+pub(crate) fn raw_varint(value: &VarInt) -> u64 {
+    match Number::closest(value.clone()) {
+        Number::Integer(value) => value as i64 as u64,
+        Number::UnsignedInteger(value) => value as u64,
+        Number::Long(value) => value as u64,
+        Number::UnsignedLong(value) => value
+    }
+}
+
 impl TryIntoJs for Value {
     fn try_into_js(&self, context: &mut Context) -> JsResult<JsValue> {
         match self {
@@ -254,4 +1324,68 @@ impl TryIntoJs for Value {
             Value::Message(value) => value.try_into_js(context)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes and converts an encoded message.
+    fn convert(buffer: &[u8]) -> SerializedMessage {
+        let decoded = protoshark::decode(buffer).unwrap();
+        SerializedMessage::from_with_depth(&decoded, Some(buffer), usize::MAX).0
+    }
+
+    #[test]
+    fn strings_which_decode_as_messages_stay_strings() {
+        // Field 1 is empty, and field 2 is "\x08\x01", which are both valid messages.
+        let message = convert(&[0x0A, 0x00, 0x12, 0x02, 0x08, 0x01]);
+
+        assert!(matches!(message.get(1), Some(Value::String(value)) if value.is_empty()));
+        assert!(matches!(message.get(2), Some(Value::String(value)) if value == "\x08\x01"));
+        assert_eq!(message.get_all(2).len(), 1);
+    }
+
+    #[test]
+    fn repeated_fields_keep_every_value() {
+        // Field 1 as the varints 1, 2, and 3, then field 2 as "ab" twice.
+        let message = convert(&[0x08, 0x01, 0x08, 0x02, 0x08, 0x03, 0x12, 0x02, b'a', b'b', 0x12, 0x02, b'a', b'b']);
+
+        let values = message.get_all(1)
+            .iter()
+            .map(|value| match value {
+                Value::VarInt(value) => raw_varint(value),
+                value => panic!("expected a varint, found {:?}", value)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, [1, 2, 3]);
+        assert!(matches!(message.get(1), Some(Value::VarInt(value)) if raw_varint(value) == 3));
+
+        let strings = message.get_all(2);
+        assert_eq!(strings.len(), 2);
+        assert!(strings.iter().all(|value| matches!(value, Value::String(value) if value == "ab")));
+    }
+
+    #[test]
+    fn repeated_messages_keep_their_encoded_bytes() {
+        // Field 1 as two nested messages holding the varint 128, which are not valid UTF-8.
+        let message = convert(&[0x0A, 0x03, 0x08, 0x80, 0x01, 0x0A, 0x03, 0x08, 0x80, 0x01]);
+
+        let nested = message.get_all(1);
+        assert_eq!(nested.len(), 2);
+        assert!(nested.iter().all(|value| matches!(value, Value::Message(nested) if nested.raw.contains_key(&1))));
+    }
+
+    #[test]
+    fn slice_ranges_are_clamped_like_array_slice() {
+        assert_eq!(slice_range(16, None, None), 0..16);
+        assert_eq!(slice_range(16, Some(4.0), Some(12.0)), 4..12);
+        assert_eq!(slice_range(16, Some(-4.0), None), 12..16);
+        assert_eq!(slice_range(16, Some(2.0), Some(-2.0)), 2..14);
+        assert_eq!(slice_range(16, Some(-40.0), Some(40.0)), 0..16);
+        assert_eq!(slice_range(16, Some(10.0), Some(3.0)), 10..10);
+        assert_eq!(slice_range(16, Some(1.9), Some(f64::NAN)), 1..1);
+        assert_eq!(slice_range(16, Some(f64::INFINITY), None), 16..16);
+        assert_eq!(slice_range(0, Some(-1.0), Some(5.0)), 0..0);
+    }
+}
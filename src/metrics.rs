@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Counters describing the work done by the matcher.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Metrics {
+    /// The number of packets provided to the matcher.
+    pub packets: u64,
+
+    /// The number of packets which failed to decode.
+    pub decode_failures: u64,
+
+    /// The number of identifications made by scripts.
+    pub identifications: u64,
+
+    /// The number of times each script was run, keyed by file name.
+    pub invocations: HashMap<String, u64>
+}
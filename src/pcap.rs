@@ -0,0 +1,86 @@
+use std::fs::File;
+use std::path::Path;
+use anyhow::anyhow;
+use pcap_parser::{create_reader, Block, PcapBlockOwned, PcapError};
+use pcap_parser::traits::PcapNGPacketBlock;
+use crate::error::BiscuitError;
+use crate::instance::Biscuit;
+use crate::matcher::Packet;
+
+/// The size of the buffer used to read capture files.
+const BUFFER_SIZE: usize = 65536;
+
+/// Counters describing a replayed capture file.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayStats {
+    /// The number of packets read from the capture.
+    pub captured: u64,
+
+    /// The number of packets the extractor returned.
+    pub extracted: u64,
+
+    /// The number of packets which failed to be processed.
+    pub failed: u64,
+
+    /// The names of packets which were newly identified, in order.
+    pub identified: Vec<String>
+}
+
+/// Reads every packet in a pcap or pcapng file, and provides it to the matcher.
+///
+/// The extractor is given the captured bytes of each packet, including any link-layer headers.
+/// Packets for which the extractor returns `None` are skipped.
+pub(crate) fn replay<F>(
+    instance: &Biscuit,
+    path: &Path,
+    mut extractor: F
+) -> Result<ReplayStats, BiscuitError>
+where
+    F: FnMut(&[u8]) -> Option<Packet>
+{
+    let file = File::open(path)
+        .map_err(|error| anyhow!("failed to open capture '{}': {}", path.to_string_lossy(), error))?;
+    let mut reader = create_reader(BUFFER_SIZE, file)
+        .map_err(|error| anyhow!("failed to read capture '{}': {:?}", path.to_string_lossy(), error))?;
+
+    let mut stats = ReplayStats::default();
+    loop {
+        match reader.next() {
+            Ok((offset, block)) => {
+                let data = match &block {
+                    PcapBlockOwned::Legacy(block) => Some(block.data),
+                    PcapBlockOwned::NG(Block::EnhancedPacket(block)) => Some(block.packet_data()),
+                    PcapBlockOwned::NG(Block::SimplePacket(block)) => Some(block.packet_data()),
+                    _ => None
+                };
+
+                if let Some(data) = data {
+                    stats.captured += 1;
+
+                    if let Some(packet) = extractor(data) {
+                        stats.extracted += 1;
+
+                        match instance.input(packet.id, packet.direction, &packet.header, &packet.data) {
+                            Ok(Some(name)) => stats.identified.push(name),
+                            Ok(None) => {},
+                            Err(error @ (BiscuitError::NotMainThread | BiscuitError::NotInitializingThread)) => {
+                                return Err(error);
+                            },
+                            Err(_) => stats.failed += 1
+                        }
+                    }
+                }
+
+                reader.consume(offset);
+            },
+            Err(PcapError::Eof) => break,
+            Err(PcapError::Incomplete(_)) => {
+                reader.refill()
+                    .map_err(|error| anyhow!("failed to read capture: {:?}", error))?;
+            },
+            Err(error) => return Err(anyhow!("failed to read capture: {:?}", error).into())
+        }
+    }
+
+    Ok(stats)
+}
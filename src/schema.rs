@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use log::warn;
+use crate::cache::MessageField;
+
+#[cfg(feature = "descriptor")]
+use prost::Message;
+#[cfg(feature = "descriptor")]
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet};
+#[cfg(feature = "descriptor")]
+use prost_types::field_descriptor_proto::Type;
+
+/// The messages and enums declared in a compiled descriptor set.
+#[cfg(feature = "descriptor")]
+#[derive(Debug, Default)]
+pub(crate) struct Descriptor {
+    /// The fields of each message, keyed by message name.
+    ///
+    /// Nested messages are named with their parents' names, such as `Outer.Inner`.
+    pub messages: HashMap<String, Vec<MessageField>>,
+
+    /// The enum value names of each enum field, keyed by message name and field ID.
+    pub enums: HashMap<String, HashMap<i32, HashMap<i64, String>>>
+}
+
+/// Parses the messages declared in a `.proto` file.
+///
+/// Only the field names, types, and IDs are read.
+/// Nested messages are named with their parents' names, such as `Outer.Inner`,
+/// and references to them are resolved to the same names.
+/// Fields with IDs which do not fit in a `u16` are skipped with a warning.
+///
+/// Returns the fields of each message, keyed by message name.
+pub(crate) fn parse_proto(source: &str) -> Result<HashMap<String, Vec<MessageField>>> {
+    let tokens = tokenize(source);
+    let mut messages = HashMap::new();
+
+    // The names of the blocks which are currently open.
+    // `None` is used for blocks which are not messages.
+    let mut blocks: Vec<Option<String>> = vec![];
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index].as_str();
+        match token {
+            "message" => {
+                let Some(name) = tokens.get(index + 1) else {
+                    return Err(anyhow!("missing message name"));
+                };
+                expect_block(&tokens, index + 2, name)?;
+
+                // Nested messages are named with their parent's name.
+                let name = match blocks.last() {
+                    Some(Some(parent)) => format!("{}.{}", parent, name),
+                    _ => name.clone()
+                };
+
+                messages.entry(name.clone()).or_insert_with(Vec::new);
+                blocks.push(Some(name));
+
+                // Skip the name and the opening brace.
+                index += 3;
+                continue;
+            },
+            "enum" | "service" | "extend" => {
+                // The contents of these blocks are not fields.
+                index = skip_block(&tokens, index)?;
+                continue;
+            },
+            "oneof" => {
+                let Some(name) = tokens.get(index + 1) else {
+                    return Err(anyhow!("missing oneof name"));
+                };
+                expect_block(&tokens, index + 2, name)?;
+
+                // Fields in a `oneof` belong to the enclosing message.
+                let parent = blocks.last().cloned().flatten();
+                blocks.push(parent);
+
+                index += 3;
+                continue;
+            },
+            "}" => {
+                blocks.pop();
+            },
+            "syntax" | "package" | "import" | "option" | "reserved" | "extensions" => {
+                index = skip_statement(&tokens, index);
+                continue;
+            },
+            _ => {
+                // Any other statement inside of a message is a field.
+                if let Some(Some(message)) = blocks.last() {
+                    let (field, next) = parse_field(&tokens, index)?;
+                    if let Some(field) = field {
+                        messages.entry(message.clone()).or_default().push(field);
+                    }
+
+                    index = next;
+                    continue;
+                }
+            }
+        }
+
+        index += 1;
+    }
+
+    // Resolve references to nested messages.
+    let names = messages.keys().cloned().collect::<Vec<_>>();
+    for (message, fields) in messages.iter_mut() {
+        for field in fields {
+            if let Some(resolved) = resolve_type(&names, message, &field.field_type) {
+                field.field_type = resolved;
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Checks that the token at the given index opens the block of a declaration.
+fn expect_block(tokens: &[String], index: usize, name: &str) -> Result<()> {
+    match tokens.get(index).map(String::as_str) {
+        Some("{") => Ok(()),
+        Some(token) => Err(anyhow!("expected '{{' after '{}', found '{}'", name, token)),
+        None => Err(anyhow!("expected '{{' after '{}'", name))
+    }
+}
+
+/// Resolves a message type referenced from the given message to its nested name.
+///
+/// Like `protoc`, the innermost enclosing scope is searched first.
+///
+/// Returns `None` if the type does not refer to a known message.
+fn resolve_type(names: &[String], message: &str, field_type: &str) -> Option<String> {
+    let mut scope = Some(message);
+    while let Some(current) = scope {
+        let candidate = format!("{}.{}", current, field_type);
+        if names.contains(&candidate) {
+            return Some(candidate);
+        }
+
+        scope = current.rsplit_once('.').map(|(parent, _)| parent);
+    }
+
+    None
+}
+
+/// Parses a field statement starting at the given token.
+///
+/// Returns the field, if the statement was a field, and the index after the statement.
+fn parse_field(tokens: &[String], start: usize) -> Result<(Option<MessageField>, usize)> {
+    let end = skip_statement(tokens, start);
+    let statement = &tokens[start..end];
+
+    // Remove the field's label.
+    let statement = match statement.first().map(String::as_str) {
+        Some("repeated" | "optional" | "required") => &statement[1..],
+        _ => statement
+    };
+
+    // Map types are split into multiple tokens.
+    let (field_type, rest) = match statement.first().map(String::as_str) {
+        Some("map") => {
+            let Some(close) = statement.iter().position(|token| token == ">") else {
+                return Err(anyhow!("unterminated map type"));
+            };
+
+            (statement[..=close].concat(), &statement[close + 1..])
+        },
+        Some(field_type) => (field_type.to_string(), &statement[1..]),
+        None => return Ok((None, end))
+    };
+
+    // The rest of the statement should be `name = id`.
+    let [field_name, equals, field_id, ..] = rest else {
+        return Ok((None, end));
+    };
+    if equals != "=" {
+        return Ok((None, end));
+    }
+
+    let Ok(field_id) = field_id.parse::<u16>() else {
+        warn!("Skipped field '{}', since its ID '{}' does not fit in 16 bits", field_name, field_id);
+        return Ok((None, end));
+    };
+
+    let field = MessageField {
+        field_name: field_name.clone(),
+        field_type,
+        field_id,
+        confidence: None
+    };
+
+    Ok((Some(field), end))
+}
+
+/// Returns the index after the statement starting at the given token.
+fn skip_statement(tokens: &[String], start: usize) -> usize {
+    match tokens[start..].iter().position(|token| token == ";") {
+        Some(offset) => start + offset + 1,
+        None => tokens.len()
+    }
+}
+
+/// Returns the index after the block starting at the given token.
+fn skip_block(tokens: &[String], start: usize) -> Result<usize> {
+    let mut depth = 0;
+    for (offset, token) in tokens[start..].iter().enumerate() {
+        match token.as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(start + offset + 1);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Err(anyhow!("unterminated block"))
+}
+
+/// Splits a `.proto` file into tokens, removing comments.
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+
+    let mut chars = source.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            // Skip comments.
+            '/' if chars.peek() == Some(&'/') => {
+                for char in chars.by_ref() {
+                    if char == '\n' {
+                        break;
+                    }
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for char in chars.by_ref() {
+                    if previous == '*' && char == '/' {
+                        break;
+                    }
+                    previous = char;
+                }
+            },
+            // Keep string literals as a single token.
+            '"' | '\'' => {
+                let mut literal = String::from(char);
+                for next in chars.by_ref() {
+                    literal.push(next);
+                    if next == char {
+                        break;
+                    }
+                }
+
+                tokens.push(literal);
+            },
+            '{' | '}' | ';' | '=' | '<' | '>' | ',' | '[' | ']' | '(' | ')' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+
+                tokens.push(char.to_string());
+            },
+            char if char.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            },
+            char => current.push(char)
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// Parses the messages declared in an encoded `FileDescriptorSet`.
+///
+/// Messages and the types of fields are named without their package,
+/// but nested declarations keep their parents' names, such as `Outer.Inner`.
+/// Map entry messages are not included, and are instead used as the types of map fields.
+///
+/// Every field is identified with full confidence, since the descriptor is authoritative.
+#[cfg(feature = "descriptor")]
+pub(crate) fn parse_descriptor(bytes: &[u8]) -> Result<Descriptor> {
+    let set = FileDescriptorSet::decode(bytes)
+        .map_err(|error| anyhow!("failed to decode descriptor set: {}", error))?;
+
+    // Find every message and enum by its fully-qualified name.
+    let mut declarations = Declarations::default();
+    for file in &set.file {
+        let scope = match file.package() {
+            "" => String::new(),
+            package => format!(".{}", package)
+        };
+
+        collect_enums(&scope, "", &file.enum_type, &mut declarations);
+        collect_messages(&scope, "", &file.message_type, &mut declarations);
+    }
+
+    let entries = declarations.messages
+        .iter()
+        .filter(|(_, message)| is_map_entry(message))
+        .map(|(name, message)| (name.as_str(), *message))
+        .collect::<HashMap<_, _>>();
+
+    let mut descriptor = Descriptor::default();
+    for (full_name, message) in &declarations.messages {
+        if is_map_entry(message) {
+            continue;
+        }
+
+        let name = declarations.names[full_name].clone();
+        let mut fields = vec![];
+        for field in &message.field {
+            let Ok(field_id) = u16::try_from(field.number()) else {
+                return Err(anyhow!("invalid ID for field '{}'", field.name()));
+            };
+
+            // Record the value names of enum fields.
+            if field.r#type() == Type::Enum {
+                if let Some(values) = declarations.enums.get(field.type_name()) {
+                    descriptor.enums
+                        .entry(name.clone())
+                        .or_default()
+                        .insert(field_id as i32, values.clone());
+                }
+            }
+
+            fields.push(MessageField {
+                field_name: field.name().to_string(),
+                field_type: field_type(field, &entries, &declarations.names),
+                field_id,
+                confidence: Some(1.0)
+            });
+        }
+
+        descriptor.messages.entry(name).or_default().extend(fields);
+    }
+
+    Ok(descriptor)
+}
+
+/// The messages and enums declared in a descriptor set, keyed by their fully-qualified names.
+#[cfg(feature = "descriptor")]
+#[derive(Default)]
+struct Declarations<'a> {
+    /// Every message, including nested messages.
+    messages: Vec<(String, &'a DescriptorProto)>,
+
+    /// The value names of every enum.
+    enums: HashMap<String, HashMap<i64, String>>,
+
+    /// The name of every message and enum without its package, such as `Outer.Inner`.
+    names: HashMap<String, String>
+}
+
+/// Returns the name of a declaration inside of the given parent, without its package.
+#[cfg(feature = "descriptor")]
+fn nested_name(parent: &str, name: &str) -> String {
+    match parent {
+        "" => name.to_string(),
+        parent => format!("{}.{}", parent, name)
+    }
+}
+
+/// Collects the value names of the given enums.
+///
+/// `scope` is the fully-qualified name of the enclosing package or message,
+/// and `parent` is the name of the enclosing message without its package.
+#[cfg(feature = "descriptor")]
+fn collect_enums(
+    scope: &str,
+    parent: &str,
+    declared: &[EnumDescriptorProto],
+    declarations: &mut Declarations
+) {
+    for declaration in declared {
+        let values = declaration.value
+            .iter()
+            .map(|value| (value.number() as i64, value.name().to_string()))
+            .collect();
+
+        let full_name = format!("{}.{}", scope, declaration.name());
+        declarations.names.insert(full_name.clone(), nested_name(parent, declaration.name()));
+        declarations.enums.insert(full_name, values);
+    }
+}
+
+/// Collects the given messages and their nested declarations.
+///
+/// `scope` is the fully-qualified name of the enclosing package or message,
+/// and `parent` is the name of the enclosing message without its package.
+#[cfg(feature = "descriptor")]
+fn collect_messages<'a>(
+    scope: &str,
+    parent: &str,
+    declared: &'a [DescriptorProto],
+    declarations: &mut Declarations<'a>
+) {
+    for message in declared {
+        let full_name = format!("{}.{}", scope, message.name());
+        let name = nested_name(parent, message.name());
+
+        collect_enums(&full_name, &name, &message.enum_type, declarations);
+        collect_messages(&full_name, &name, &message.nested_type, declarations);
+
+        declarations.names.insert(full_name.clone(), name);
+        declarations.messages.push((full_name, message));
+    }
+}
+
+/// Checks if a message was generated for the entries of a map field.
+#[cfg(feature = "descriptor")]
+fn is_map_entry(message: &DescriptorProto) -> bool {
+    message.options
+        .as_ref()
+        .is_some_and(|options| options.map_entry())
+}
+
+/// Returns the type of a field, as it would be written in a `.proto` file.
+///
+/// Message and enum types are named without their package, but with their parents' names.
+#[cfg(feature = "descriptor")]
+fn field_type(
+    field: &FieldDescriptorProto,
+    entries: &HashMap<&str, &DescriptorProto>,
+    names: &HashMap<String, String>
+) -> String {
+    let scalar = match field.r#type() {
+        Type::Double => "double",
+        Type::Float => "float",
+        Type::Int64 => "int64",
+        Type::Uint64 => "uint64",
+        Type::Int32 => "int32",
+        Type::Fixed64 => "fixed64",
+        Type::Fixed32 => "fixed32",
+        Type::Bool => "bool",
+        Type::String => "string",
+        Type::Bytes => "bytes",
+        Type::Uint32 => "uint32",
+        Type::Sfixed32 => "sfixed32",
+        Type::Sfixed64 => "sfixed64",
+        Type::Sint32 => "sint32",
+        Type::Sint64 => "sint64",
+        Type::Group | Type::Message | Type::Enum => {
+            // Map fields refer to a generated entry message.
+            if let Some(entry) = entries.get(field.type_name()) {
+                let key = entry.field.iter().find(|field| field.number() == 1);
+                let value = entry.field.iter().find(|field| field.number() == 2);
+                if let (Some(key), Some(value)) = (key, value) {
+                    let key = field_type(key, entries, names);
+                    let value = field_type(value, entries, names);
+                    return format!("map<{},{}>", key, value);
+                }
+            }
+
+            // Types which are not declared in the set keep their last name.
+            let name = field.type_name();
+            return match names.get(name) {
+                Some(name) => name.clone(),
+                None => name.rsplit('.').next().unwrap_or(name).to_string()
+            };
+        }
+    };
+
+    scalar.to_string()
+}
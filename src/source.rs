@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::Result;
+
+/// The sources of scripts which were provided in memory, keyed by file path.
+///
+/// These only last for a single call to `initialize_from_sources`,
+/// so they never shadow the files of scripts loaded from a directory later.
+/// Scripts which are not provided in memory are read from disk every time they are loaded.
+///
+/// Parsed scripts are not cached. A parsed `boa_engine::Script` is bound to the realm it was
+/// parsed in, and every load creates new contexts, so it cannot be evaluated again after a reload.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScriptSources(Arc<HashMap<PathBuf, Arc<[u8]>>>);
+
+impl ScriptSources {
+    /// Reads the contents of the script at the given path.
+    ///
+    /// Sources provided in memory are returned without reading the file.
+    pub fn read(&self, path: &Path) -> Result<Arc<[u8]>> {
+        if let Some(contents) = self.0.get(path) {
+            return Ok(contents.clone());
+        }
+
+        Ok(std::fs::read(path)?.into())
+    }
+}
+
+impl From<HashMap<PathBuf, Arc<[u8]>>> for ScriptSources {
+    fn from(sources: HashMap<PathBuf, Arc<[u8]>>) -> Self {
+        ScriptSources(Arc::new(sources))
+    }
+}
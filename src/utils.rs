@@ -1,11 +1,25 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use paste::paste;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
-use boa_engine::{Context, JsResult, JsValue, JsNativeError};
-use boa_engine::object::builtins::JsArrayBuffer;
+use boa_engine::{js_string, Context, Finalize, JsArgs, JsData, JsResult, JsValue, JsNativeError, Trace};
+use boa_engine::class::Class;
+use boa_engine::object::builtins::{JsArray, JsArrayBuffer};
 use boa_engine::value::{TryIntoJs, Type};
+use aes_gcm::{Aes128Gcm, Aes256Gcm, KeyInit, Nonce};
+use aes_gcm::aead::{Aead, Payload};
+use crc::{Algorithm, Crc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use rsa::pkcs1::DecodeRsaPrivateKey;
 use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use log::LevelFilter;
+use crate::matcher::Env;
+use crate::message::{raw_varint, SerializedMessage, Value};
+use crate::wire;
 
 /// Macro utility to fetch a value from the global context.
 ///
@@ -13,7 +27,7 @@ use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
 ///
 /// # Example
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// use boa_engine::Context;
 /// use biscuit::js_get;
 ///
@@ -41,7 +55,7 @@ macro_rules! js_get {
 /// 
 /// # Example
 /// 
-/// ```rust,no_run
+/// ```rust,ignore
 /// use boa_engine::JsValue;
 /// use biscuit::js_convert;
 /// 
@@ -65,7 +79,7 @@ macro_rules! js_convert {
 /// 
 /// # Example
 /// 
-/// ```rust,no_run
+/// ```rust,ignore
 /// use boa_engine::{Context, Source};
 /// use biscuit::js_catch;
 ///
@@ -87,7 +101,7 @@ macro_rules! js_catch {
 /// 
 /// # Example
 /// 
-/// ```rust,no_run
+/// ```rust,ignore
 /// use biscuit::js_error;
 /// 
 /// let error = js_error!("this is an error");
@@ -105,8 +119,8 @@ macro_rules! js_error {
 /// 
 /// # Example
 /// 
-/// ```rust,no_run
-/// use boa_engine::{Context, Finalize, JsData, Trace};
+/// ```rust,ignore
+/// use boa_engine::{js_string, Context, Finalize, JsData, Trace};
 /// use biscuit::from_realm;
 ///
 /// #[derive(Trace, Finalize, JsData)]
@@ -163,6 +177,14 @@ pub(crate) fn js_stringify(value: &JsValue, context: &mut Context) -> String {
         }
         Type::Object => {
             match value.as_object() {
+                // Messages are printed as their fields.
+                Some(object) if object.is::<SerializedMessage>() => {
+                    let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+                        return "undefined".to_string();
+                    };
+
+                    message.to_json().to_string()
+                },
                 Some(object) => {
                     let Ok(value) = object.try_into_js(context) else {
                         return "undefined".to_string();
@@ -179,16 +201,33 @@ pub(crate) fn js_stringify(value: &JsValue, context: &mut Context) -> String {
     }
 }
 
+/// Represents the most verbose level which a script may log at in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+pub(crate) struct JsLogLevel(#[unsafe_ignore_trace] pub LevelFilter);
+
+/// Generates JavaScript-compatible logging functions.
+///
+/// Like `console.log`, every argument is logged, separated by spaces.
 macro_rules! js_log {
     ($($level:ident),*) => {
         $(
             paste! {
                 pub(crate) fn [<js_ $level>](_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
-                    let Some(message) = args.get(0) else {
-                        return js_error!("missing message argument");
+                    // Skip messages which are filtered out.
+                    let filter = {
+                        let realm = context.realm().host_defined();
+                        from_realm!(realm => JsLogLevel).0
                     };
+                    if log::Level::[<$level:camel>] > filter {
+                        return Ok(JsValue::Undefined);
+                    }
+
+                    let string = args
+                        .iter()
+                        .map(|arg| js_stringify(arg, context))
+                        .collect::<Vec<_>>()
+                        .join(" ");
 
-                    let string = js_stringify(message, context);
                     log::$level!("{}", string);
 
                     Ok(JsValue::Undefined)
@@ -212,7 +251,7 @@ pub(crate) fn js_base64_decode(
     args: &[JsValue],
     context: &mut Context
 ) -> JsResult<JsValue> {
-    let Some(value) = args.get(0) else {
+    let Some(value) = args.first() else {
         return js_error!("missing value argument");
     };
 
@@ -251,7 +290,7 @@ pub(crate) fn js_rsa_decrypt(
     args: &[JsValue],
     context: &mut Context
 ) -> JsResult<JsValue> {
-    let Some(private_key) = args.get(0) else {
+    let Some(private_key) = args.first() else {
         return js_error!("missing private key argument");
     };
     let Some(encrypted) = args.get(1) else {
@@ -284,3 +323,597 @@ pub(crate) fn js_rsa_decrypt(
 
     Ok(buffer.into())
 }
+
+/// AES-GCM decryption method that is JavaScript compatible.
+///
+/// The key must be 16 or 32 bytes long, and the nonce must be 12 bytes long.
+/// If the tag is not provided separately, it must be appended to the ciphertext.
+///
+/// Each value can be an `ArrayBuffer` or a Base64-encoded string.
+///
+/// # Example
+///
+/// ```js
+/// try {
+///     const result = aesGcmDecrypt(key, nonce, ciphertext, aad);
+/// } catch (e) {
+///     error("Failed to decrypt message:", e);
+/// }
+/// ```
+pub(crate) fn js_aes_gcm_decrypt(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(key) = args.first() else {
+        return js_error!("missing key argument");
+    };
+    let Some(nonce) = args.get(1) else {
+        return js_error!("missing nonce argument");
+    };
+    let Some(ciphertext) = args.get(2) else {
+        return js_error!("missing ciphertext argument");
+    };
+
+    let key = js_binary(key, context)?;
+    let nonce = js_binary(nonce, context)?;
+    let mut ciphertext = js_binary(ciphertext, context)?;
+
+    let aad = match args.get(3) {
+        Some(aad) if !aad.is_null_or_undefined() => js_binary(aad, context)?,
+        _ => vec![]
+    };
+
+    // Append the tag, if it was provided separately.
+    if let Some(tag) = args.get(4) {
+        if !tag.is_null_or_undefined() {
+            ciphertext.extend(js_binary(tag, context)?);
+        }
+    }
+
+    if nonce.len() != 12 {
+        return js_error!(format!("invalid nonce length: expected 12 bytes, got {}", nonce.len()));
+    }
+    let nonce = Nonce::from_slice(&nonce);
+    let payload = Payload { msg: &ciphertext, aad: &aad };
+
+    // The cipher is chosen by the length of the key.
+    let decrypted = match key.len() {
+        16 => Aes128Gcm::new_from_slice(&key)
+            .map(|cipher| cipher.decrypt(nonce, payload)),
+        32 => Aes256Gcm::new_from_slice(&key)
+            .map(|cipher| cipher.decrypt(nonce, payload)),
+        length => return js_error!(format!("invalid key length: expected 16 or 32 bytes, got {}", length))
+    };
+
+    let Ok(decrypted) = decrypted else {
+        return js_error!("invalid key");
+    };
+    let Ok(decrypted) = decrypted else {
+        return js_error!("tag verification failed");
+    };
+
+    // Convert the Rust byte array into a JavaScript array buffer.
+    let buffer = JsArrayBuffer::from_byte_block(decrypted, context)?;
+
+    Ok(buffer.into())
+}
+
+/// Signature verification method that is JavaScript compatible.
+///
+/// Only the `ed25519` algorithm is supported.
+/// Each value can be an `ArrayBuffer` or a Base64-encoded string.
+///
+/// Returns `false` if the signature does not match,
+/// but throws if the key or signature is malformed.
+///
+/// # Example
+///
+/// ```js
+/// if (verifySignature("ed25519", publicKey, data.raw(1), data.raw(2))) {
+///     info("Found the signature field.");
+/// }
+/// ```
+pub(crate) fn js_verify_signature(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(algorithm) = args.first() else {
+        return js_error!("missing algorithm argument");
+    };
+    let Some(public_key) = args.get(1) else {
+        return js_error!("missing public key argument");
+    };
+    let Some(message) = args.get(2) else {
+        return js_error!("missing message argument");
+    };
+    let Some(signature) = args.get(3) else {
+        return js_error!("missing signature argument");
+    };
+
+    let algorithm = algorithm.to_string(context)?
+        .to_std_string_escaped();
+    let public_key = js_binary(public_key, context)?;
+    let message = js_binary(message, context)?;
+    let signature = js_binary(signature, context)?;
+
+    match algorithm.to_lowercase().as_str() {
+        "ed25519" => {
+            let Ok(public_key) = <[u8; 32]>::try_from(public_key.as_slice()) else {
+                return js_error!(format!("invalid public key length: expected 32 bytes, got {}", public_key.len()));
+            };
+            let Ok(public_key) = VerifyingKey::from_bytes(&public_key) else {
+                return js_error!("invalid public key");
+            };
+            let Ok(signature) = Signature::from_slice(&signature) else {
+                return js_error!(format!("invalid signature length: expected 64 bytes, got {}", signature.len()));
+            };
+
+            Ok(JsValue::Boolean(public_key.verify(&message, &signature).is_ok()))
+        },
+        _ => js_error!(format!("unsupported signature algorithm: {}", algorithm))
+    }
+}
+
+/// Fetches the current time as milliseconds since the Unix epoch.
+///
+/// # Example
+///
+/// ```js
+/// const start = now();
+/// // ...
+/// info(`Took ${now() - start}ms.`);
+/// ```
+pub(crate) fn js_now(
+    _: &JsValue,
+    _: &[JsValue],
+    _: &mut Context
+) -> JsResult<JsValue> {
+    let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return js_error!("system time is before the Unix epoch");
+    };
+
+    Ok(JsValue::from(duration.as_millis() as f64))
+}
+
+/// Represents a script's random number generator in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+pub(crate) struct JsRandom(#[unsafe_ignore_trace] pub RefCell<StdRng>);
+
+impl JsRandom {
+    /// Creates a generator from the given seed.
+    ///
+    /// If no seed is provided, the generator is seeded randomly.
+    pub fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy()
+        };
+
+        JsRandom(RefCell::new(rng))
+    }
+}
+
+/// Generates a random number between `0` (inclusive) and `1` (exclusive).
+///
+/// Unlike `Math.random`, this is reproducible when `Config::rng_seed` is set.
+///
+/// # Example
+///
+/// ```js
+/// if (random() < 0.1) {
+///     info("Sampled packet.");
+/// }
+/// ```
+pub(crate) fn js_random(
+    _: &JsValue,
+    _: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let realm = context.realm().host_defined_mut();
+    let random = from_realm!(realm => JsRandom);
+
+    let value: f64 = random.0.borrow_mut().gen();
+    Ok(JsValue::from(value))
+}
+
+/// Represents the environment variables in a JavaScript realm.
+#[derive(Trace, Finalize, JsData)]
+pub(crate) struct JsEnv(#[unsafe_ignore_trace] pub Arc<Env>);
+
+/// Fetches an environment variable.
+///
+/// Returns `undefined` if the variable does not exist.
+///
+/// # Example
+///
+/// ```js
+/// const key = getEnv("PRIVATE_KEY");
+/// if (key === undefined) {
+///     warn("No private key was provided.");
+/// }
+/// ```
+pub(crate) fn js_get_env(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(name) = args.first() else {
+        return js_error!("missing name argument");
+    };
+    let name = name.to_string(context)?
+        .to_std_string_escaped();
+
+    let realm = context.realm().host_defined_mut();
+    let env = from_realm!(realm => JsEnv);
+
+    match env.0.get(&name) {
+        Some(value) => Ok(js_string!(value.clone()).into()),
+        None => Ok(JsValue::Undefined)
+    }
+}
+
+/// Fetches the environment variable named by the first argument.
+fn env_value(args: &[JsValue], context: &mut Context) -> JsResult<Option<String>> {
+    let Some(name) = args.first() else {
+        return js_error!("missing name argument");
+    };
+    let name = name.to_string(context)?
+        .to_std_string_escaped();
+
+    let realm = context.realm().host_defined_mut();
+    let env = from_realm!(realm => JsEnv);
+
+    Ok(env.0.get(&name).cloned())
+}
+
+/// Fetches an environment variable as a number.
+///
+/// Returns the default, or `undefined` if none was given,
+/// if the variable does not exist or is not a number.
+///
+/// # Example
+///
+/// ```js
+/// const retries = getEnvNumber("MAX_RETRIES", 3);
+/// ```
+pub(crate) fn js_get_env_number(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let value = env_value(args, context)?;
+
+    match value.and_then(|value| value.trim().parse::<f64>().ok()) {
+        Some(number) => Ok(JsValue::from(number)),
+        None => Ok(args.get_or_undefined(1).clone())
+    }
+}
+
+/// Fetches an environment variable as a boolean.
+///
+/// `true`, `1`, and `yes` are true, while `false`, `0`, and `no` are false, ignoring case.
+/// Returns the default, or `undefined` if none was given,
+/// if the variable does not exist or is not one of these values.
+///
+/// # Example
+///
+/// ```js
+/// if (getEnvBool("VERBOSE", false)) {
+///     info("Verbose logging is enabled.");
+/// }
+/// ```
+pub(crate) fn js_get_env_bool(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let value = env_value(args, context)?;
+
+    let value = value.and_then(|value| match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None
+    });
+
+    match value {
+        Some(value) => Ok(JsValue::from(value)),
+        None => Ok(args.get_or_undefined(1).clone())
+    }
+}
+
+/// Copies the contents of a JavaScript `ArrayBuffer` into a Rust byte array.
+pub(crate) fn js_bytes(value: &JsValue) -> JsResult<Vec<u8>> {
+    let Some(object) = value.as_object() else {
+        return js_error!("expected an ArrayBuffer");
+    };
+
+    let buffer = JsArrayBuffer::from_object(object.clone())?;
+    let Some(data) = buffer.data() else {
+        return js_error!("ArrayBuffer is detached");
+    };
+
+    Ok(data.to_vec())
+}
+
+/// Reads binary data from either an `ArrayBuffer` or a Base64-encoded string.
+pub(crate) fn js_binary(value: &JsValue, context: &mut Context) -> JsResult<Vec<u8>> {
+    if !value.is_string() {
+        return js_bytes(value);
+    }
+
+    let string = value.to_string(context)?
+        .to_std_string_escaped();
+    let Ok(bytes) = BASE64_STANDARD.decode(string) else {
+        return js_error!("failed to decode base64");
+    };
+
+    Ok(bytes)
+}
+
+/// Reads a protobuf-style varint from a buffer.
+///
+/// Returns an array of the value and the number of bytes read.
+///
+/// # Example
+///
+/// ```js
+/// const [length, read] = readVarInt(buffer, 0);
+/// const payload = buffer.slice(read, read + length);
+/// ```
+pub(crate) fn js_read_varint(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let offset = match args.get(1) {
+        Some(offset) => offset.to_index(context)? as usize,
+        None => 0
+    };
+
+    let Some((value, read)) = wire::read_varint(&bytes, offset) else {
+        return js_error!("failed to read varint");
+    };
+
+    let result = JsArray::new(context);
+    result.push(value.try_into_js(context)?, context)?;
+    result.push(JsValue::from(read as u32), context)?;
+
+    Ok(result.into())
+}
+
+/// Decodes a buffer as a protobuf message.
+///
+/// Throws if the buffer is not a valid message.
+/// The error describes the problem, such as a truncated field or an invalid wire type.
+///
+/// # Example
+///
+/// ```js
+/// const decrypted = rsaDecrypt(privateKey, data.string(1));
+/// const inner = decodePacket(decrypted);
+/// ```
+pub(crate) fn js_decode_packet(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let decoded = match protoshark::decode(&bytes) {
+        Ok(decoded) => decoded,
+        Err(error) => {
+            // Describe the problem in terms of the wire format, if possible.
+            let reason = wire::diagnose(&bytes).unwrap_or_else(|| format!("{:?}", error));
+            return js_error!(format!("failed to decode packet: {}", reason));
+        }
+    };
+
+    let message = SerializedMessage::from_script(context, &decoded, &bytes);
+    Ok(SerializedMessage::from_data(message, context)?.into())
+}
+
+/// Decodes a buffer as UTF-8 text.
+///
+/// Invalid sequences are replaced with `U+FFFD`.
+///
+/// # Example
+///
+/// ```js
+/// const name = decodeUtf8(data.raw(2));
+/// ```
+pub(crate) fn js_decode_utf8(
+    _: &JsValue,
+    args: &[JsValue],
+    _: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(JsValue::from(js_string!(text.as_ref())))
+}
+
+/// Decodes a buffer as Latin-1 (ISO-8859-1) text.
+///
+/// Every byte maps directly to the code point of the same value, so this cannot fail.
+///
+/// # Example
+///
+/// ```js
+/// const name = decodeLatin1(data.raw(2));
+/// ```
+pub(crate) fn js_decode_latin1(
+    _: &JsValue,
+    args: &[JsValue],
+    _: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let text = bytes.iter().map(|byte| *byte as char).collect::<String>();
+    Ok(JsValue::from(js_string!(text)))
+}
+
+/// Finds a CRC-32 algorithm by its preset name.
+fn crc32_preset(name: &str) -> Option<&'static Algorithm<u32>> {
+    let algorithm = match name.to_uppercase().replace('-', "_").as_str() {
+        "ISO_HDLC" => &crc::CRC_32_ISO_HDLC,
+        "BZIP2" => &crc::CRC_32_BZIP2,
+        "CKSUM" => &crc::CRC_32_CKSUM,
+        "ISCSI" => &crc::CRC_32_ISCSI,
+        "JAMCRC" => &crc::CRC_32_JAMCRC,
+        "MPEG_2" => &crc::CRC_32_MPEG_2,
+        "XFER" => &crc::CRC_32_XFER,
+        _ => return None
+    };
+
+    Some(algorithm)
+}
+
+/// Finds a CRC-16 algorithm by its preset name.
+fn crc16_preset(name: &str) -> Option<&'static Algorithm<u16>> {
+    let algorithm = match name.to_uppercase().replace('-', "_").as_str() {
+        "ARC" => &crc::CRC_16_ARC,
+        "IBM_3740" => &crc::CRC_16_IBM_3740,
+        "KERMIT" => &crc::CRC_16_KERMIT,
+        "MODBUS" => &crc::CRC_16_MODBUS,
+        "USB" => &crc::CRC_16_USB,
+        "XMODEM" => &crc::CRC_16_XMODEM,
+        _ => return None
+    };
+
+    Some(algorithm)
+}
+
+/// Reads the optional preset name argument of a checksum function.
+fn js_crc_preset(args: &[JsValue], default: &str, context: &mut Context) -> JsResult<String> {
+    match args.get(1) {
+        Some(preset) if !preset.is_undefined() => {
+            Ok(preset.to_string(context)?.to_std_string_escaped())
+        },
+        _ => Ok(default.to_string())
+    }
+}
+
+/// Calculates the CRC-32 checksum of a buffer.
+///
+/// The algorithm can be chosen with an optional preset name,
+/// such as `ISO_HDLC` (the default), `BZIP2`, or `ISCSI`.
+///
+/// # Example
+///
+/// ```js
+/// const checksum = crc32(payload);
+/// const castagnoli = crc32(payload, "ISCSI");
+/// ```
+pub(crate) fn js_crc32(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let preset = js_crc_preset(args, "ISO_HDLC", context)?;
+    let Some(algorithm) = crc32_preset(&preset) else {
+        return js_error!(format!("unknown CRC-32 preset: {}", preset));
+    };
+
+    let checksum = Crc::<u32>::new(algorithm).checksum(&bytes);
+    Ok(JsValue::from(checksum))
+}
+
+/// Calculates the CRC-16 checksum of a buffer.
+///
+/// The algorithm can be chosen with an optional preset name,
+/// such as `ARC` (the default), `IBM_3740`, or `XMODEM`.
+///
+/// # Example
+///
+/// ```js
+/// const checksum = crc16(payload, "MODBUS");
+/// ```
+pub(crate) fn js_crc16(
+    _: &JsValue,
+    args: &[JsValue],
+    context: &mut Context
+) -> JsResult<JsValue> {
+    let Some(buffer) = args.first() else {
+        return js_error!("missing buffer argument");
+    };
+    let bytes = js_bytes(buffer)?;
+
+    let preset = js_crc_preset(args, "ARC", context)?;
+    let Some(algorithm) = crc16_preset(&preset) else {
+        return js_error!(format!("unknown CRC-16 preset: {}", preset));
+    };
+
+    let checksum = Crc::<u16>::new(algorithm).checksum(&bytes);
+    Ok(JsValue::from(checksum))
+}
+
+/// Suggests a protobuf type for a field of a `SerializedMessage`.
+///
+/// The type is derived from how the field was encoded, so it is always accepted by `identify`.
+/// Varints are suggested as `int64`, unless they are too large for it.
+/// Nested messages are suggested as `bytes`, since their name is not known.
+/// Fields which occur more than once are suggested as `repeated`.
+///
+/// Returns `undefined` if the field does not exist.
+///
+/// # Example
+///
+/// ```js
+/// identify("PlayerMove", id, { field_name: "speed", field_type: inferType(data, 3), field_id: 3 });
+/// ```
+pub(crate) fn js_infer_type(
+    _: &JsValue,
+    args: &[JsValue],
+    _: &mut Context
+) -> JsResult<JsValue> {
+    let object = js_convert!(args.get_or_undefined(0), as_object);
+    let Some(message) = object.downcast_ref::<SerializedMessage>() else {
+        return js_error!("expected a message");
+    };
+    let field_id = js_convert!(args.get_or_undefined(1), as_number) as i32;
+
+    let values = message.get_all(field_id);
+    let Some(value) = values.first() else {
+        return Ok(JsValue::Undefined);
+    };
+
+    let field_type = match value {
+        Value::VarInt(_) => match values
+            .iter()
+            .any(|value| matches!(value, Value::VarInt(value) if raw_varint(value) > i64::MAX as u64))
+        {
+            true => "uint64",
+            false => "int64"
+        },
+        Value::Float(_) => "float",
+        Value::Double(_) => "double",
+        Value::String(_) => "string",
+        Value::Bytes(_) | Value::Message(_) => "bytes"
+    };
+
+    Ok(JsValue::from(match values.len() {
+        1 => js_string!(field_type),
+        _ => js_string!(format!("repeated {}", field_type))
+    }))
+}
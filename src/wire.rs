@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+/// A single field read directly from the protobuf wire format.
+#[derive(Debug, Clone)]
+pub(crate) struct WireField<'a> {
+    /// The encoded value of the last occurrence of the field.
+    ///
+    /// For length-delimited fields, this excludes the length prefix.
+    pub data: &'a [u8],
+
+    /// The encoded value of every occurrence of the field, in order.
+    pub values: Vec<&'a [u8]>,
+
+    /// The number of times the field occurs in the message.
+    ///
+    /// A packed repeated field is counted as a single occurrence,
+    /// since its element type is not known.
+    pub count: usize
+}
+
+/// Reads a varint from the buffer at the given offset.
+///
+/// Returns the value and the number of bytes read,
+/// or `None` if the varint is truncated or too long.
+pub(crate) fn read_varint(buffer: &[u8], offset: usize) -> Option<(u64, usize)> {
+    let bytes = buffer.get(offset..)?;
+
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().take(10).enumerate() {
+        value |= ((byte & 0x7F) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+
+    None
+}
+
+/// Reads the field starting at the given offset.
+///
+/// Returns the field's ID, the field's encoded value, and the offset after the field,
+/// or `None` if the field is malformed.
+fn read_field(buffer: &[u8], offset: usize) -> Option<(i32, &[u8], usize)> {
+    // Read the field's tag.
+    let (tag, read) = read_varint(buffer, offset)?;
+    let offset = offset + read;
+
+    let field_id = (tag >> 3) as i32;
+    let wire_type = (tag & 0x7) as u8;
+
+    // Find the bounds of the field's value.
+    let (start, end) = match wire_type {
+        0 => {
+            let (_, read) = read_varint(buffer, offset)?;
+            (offset, offset + read)
+        },
+        1 => (offset, offset + 8),
+        2 => {
+            let (length, read) = read_varint(buffer, offset)?;
+            let start = offset + read;
+            (start, start.checked_add(length as usize)?)
+        },
+        5 => (offset, offset + 4),
+        _ => return None
+    };
+
+    let data = buffer.get(start..end)?;
+    Some((field_id, data, end))
+}
+
+/// Splits an encoded message into its fields.
+///
+/// If a field is repeated, `data` holds the last occurrence,
+/// and `values` holds every occurrence.
+///
+/// Returns `None` if the message is malformed.
+pub(crate) fn split_fields(buffer: &[u8]) -> Option<HashMap<i32, WireField<'_>>> {
+    let mut fields: HashMap<i32, WireField<'_>> = HashMap::new();
+
+    let mut offset = 0;
+    while offset < buffer.len() {
+        let (field_id, data, end) = read_field(buffer, offset)?;
+        let field = fields.entry(field_id).or_insert_with(|| WireField {
+            data,
+            values: vec![],
+            count: 0
+        });
+        field.data = data;
+        field.values.push(data);
+        field.count += 1;
+
+        offset = end;
+    }
+
+    Some(fields)
+}
+
+/// Describes why an encoded message is malformed.
+///
+/// Returns `None` if no problem was found in the message's structure.
+pub(crate) fn diagnose(buffer: &[u8]) -> Option<String> {
+    let mut offset = 0;
+    while offset < buffer.len() {
+        // Read the field's tag.
+        let Some((tag, read)) = read_varint(buffer, offset) else {
+            return Some(format!("truncated field tag at byte {}", offset));
+        };
+
+        let field_id = tag >> 3;
+        let wire_type = (tag & 0x7) as u8;
+        if field_id == 0 {
+            return Some(format!("invalid field ID 0 at byte {}", offset));
+        }
+
+        let start = offset + read;
+        let remaining = buffer.len() - start;
+
+        // Check that the field's value fits in the buffer.
+        let end = match wire_type {
+            0 => match read_varint(buffer, start) {
+                Some((_, read)) => start + read,
+                None => return Some(format!("truncated varint in field {} at byte {}", field_id, start))
+            },
+            1 | 5 => {
+                let size = if wire_type == 1 { 8 } else { 4 };
+                if remaining < size {
+                    return Some(format!(
+                        "truncated {}-bit value in field {}: expected {} bytes, found {}",
+                        size * 8, field_id, size, remaining
+                    ));
+                }
+
+                start + size
+            },
+            2 => {
+                let Some((length, read)) = read_varint(buffer, start) else {
+                    return Some(format!("truncated length of field {} at byte {}", field_id, start));
+                };
+
+                let available = remaining - read;
+                if length > available as u64 {
+                    return Some(format!(
+                        "truncated field {}: length is {} bytes, but only {} remain",
+                        field_id, length, available
+                    ));
+                }
+
+                start + read + length as usize
+            },
+            // Groups are rejected by the decoder, so they are reported without being skipped.
+            3 | 4 => return Some(format!(
+                "unsupported group wire type {} in field {} at byte {}",
+                wire_type, field_id, offset
+            )),
+            _ => return Some(format!(
+                "invalid wire type {} in field {} at byte {}",
+                wire_type, field_id, offset
+            ))
+        };
+
+        offset = end;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_groups_are_rejected_without_recursing() {
+        // Every byte is a start group tag for field 1.
+        let buffer = vec![0x0B; 1 << 20];
+
+        assert!(split_fields(&buffer).is_none());
+        assert_eq!(
+            diagnose(&buffer).as_deref(),
+            Some("unsupported group wire type 3 in field 1 at byte 0")
+        );
+        assert!(protoshark::decode(&buffer).is_err());
+    }
+
+    #[test]
+    fn repeated_fields_keep_every_occurrence() {
+        // Field 1 as the varints 1, 2, and 3, with field 2 in between.
+        let buffer = [0x08, 0x01, 0x08, 0x02, 0x12, 0x01, 0x41, 0x08, 0x03];
+        let fields = split_fields(&buffer).unwrap();
+
+        let field = &fields[&1];
+        assert_eq!(field.count, 3);
+        assert_eq!(field.values, [&[0x01][..], &[0x02], &[0x03]]);
+        assert_eq!(field.data, [0x03]);
+
+        assert_eq!(fields[&2].values, [b"A"]);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+use anyhow::{anyhow, Result};
+use crate::matcher::{load_comparers, Comparer, HostState};
+
+/// A unit of work which is run against a worker's comparers.
+type Task = Box<dyn FnOnce(&mut Vec<Comparer>) + Send>;
+
+/// A worker is a thread which owns a group of comparers.
+///
+/// JavaScript contexts are not thread-safe, so each worker creates
+/// and runs its comparers exclusively on its own thread.
+pub(crate) struct Worker {
+    sender: Option<Sender<Task>>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl Worker {
+    /// Spawns a worker which loads the given scripts.
+    ///
+    /// This blocks until the worker has finished loading its comparers.
+    pub fn spawn(
+        index: usize,
+        path: PathBuf,
+        scripts: Vec<PathBuf>,
+        host: HostState
+    ) -> Result<Self> {
+        let (sender, receiver) = channel::<Task>();
+        let (loaded, on_loaded) = channel::<Result<()>>();
+
+        let handle = std::thread::Builder::new()
+            .name(format!("biscuit-worker-{}", index))
+            .spawn(move || {
+                // Load the comparers on the worker's thread.
+                let mut comparers = match load_comparers(&path, &scripts, &host) {
+                    Ok(comparers) => {
+                        let _ = loaded.send(Ok(()));
+                        comparers
+                    },
+                    Err(error) => {
+                        let _ = loaded.send(Err(error));
+                        return;
+                    }
+                };
+
+                // Run tasks until the worker is dropped.
+                for task in receiver {
+                    task(&mut comparers);
+                }
+            })?;
+
+        // Wait for the worker to finish loading.
+        match on_loaded.recv() {
+            Ok(Ok(())) => {},
+            Ok(Err(error)) => return Err(error),
+            Err(_) => return Err(anyhow!("worker {} exited while loading scripts", index))
+        };
+
+        Ok(Worker {
+            sender: Some(sender),
+            handle: Some(handle)
+        })
+    }
+
+    /// Runs the given task on the worker's comparers.
+    ///
+    /// The result of the task is sent to the returned receiver.
+    pub fn execute<R, F>(&self, task: F) -> Receiver<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut Vec<Comparer>) -> R + Send + 'static
+    {
+        let (sender, receiver) = channel();
+
+        if let Some(worker) = &self.sender {
+            let _ = worker.send(Box::new(move |comparers: &mut Vec<Comparer>| {
+                let _ = sender.send(task(comparers));
+            }));
+        }
+
+        receiver
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // Closing the channel stops the worker's loop.
+        drop(self.sender.take());
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Debug for Worker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Worker")
+            .field("running", &self.sender.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,694 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use biscuit::config::Config;
+use biscuit::{Biscuit, BiscuitError, Direction, FeedOutcome};
+
+/// Creates an instance which runs the given scripts on the calling thread.
+fn instance(scripts: &[(&str, &str)]) -> Biscuit {
+    let config = Config {
+        allow_any_thread: true,
+        worker_threads: 0,
+        ..Config::default()
+    };
+
+    let sources = scripts
+        .iter()
+        .map(|(name, source)| (name.to_string(), source.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let instance = Biscuit::new();
+    instance.initialize_from_sources(config, sources)
+        .expect("failed to load test scripts");
+
+    instance
+}
+
+/// Encodes a message with a single varint field.
+fn varint_message(field_id: u8, mut value: u64) -> Vec<u8> {
+    let mut data = vec![field_id << 3];
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+
+    data
+}
+
+/// Writes files into a new temporary directory, returning its path.
+///
+/// File names may contain directories, which are created as needed.
+fn files_on_disk(label: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("biscuit-{}-{}", label, std::process::id()));
+    for (name, contents) in files {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    root
+}
+
+/// Creates an instance which runs the scripts in the given directory on the calling thread.
+fn instance_from_directory(scripts: &Path) -> Result<Biscuit, BiscuitError> {
+    let config = Config {
+        script_path: scripts.to_string_lossy().to_string(),
+        allow_any_thread: true,
+        ..Config::default()
+    };
+
+    let instance = Biscuit::new();
+    instance.initialize(config)?;
+
+    Ok(instance)
+}
+
+#[test]
+fn panicking_script_does_not_break_later_input() {
+    let instance = instance(&[
+        // Converting `undefined` nested in an object panics inside the script engine.
+        ("panics.js", r#"
+            var PACKET_NAME = "Panics";
+            function compare(id, header, data) {
+                shared.set("value", { nested: undefined });
+                return false;
+            }
+        "#),
+        ("login.js", r#"
+            var PACKET_NAME = "Login";
+            function compare(id, header, data) {
+                if (data.varint(1) !== 7) return false;
+                identify("Login", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#)
+    ]);
+
+    // The first packet runs the panicking script, which is reported as an error.
+    let result = instance.input(1, Direction::Unknown, &[], &varint_message(1, 1));
+    assert!(result.is_ok());
+    let errors = instance.recent_errors();
+    assert!(errors.iter().any(|error| error.script.ends_with("panics.js") && error.message.contains("panicked")));
+
+    // Later packets are still compared by the other script.
+    let result = instance.input(2, Direction::Unknown, &[], &varint_message(1, 7));
+    assert_eq!(result.unwrap().as_deref(), Some("Login"));
+}
+
+#[test]
+fn bigint_preserves_values_above_2_pow_53() {
+    let instance = instance(&[
+        ("uid.js", r#"
+            var PACKET_NAME = "Uid";
+            function compare(id, header, data) {
+                if (data.bigint(1) !== 9007199254740993n) return false;
+                identify("Uid", id, { field_name: "uid", field_type: "uint64", field_id: 1 });
+                return true;
+            }
+        "#)
+    ]);
+
+    // This is 2^53 + 1, which cannot be represented as a JavaScript number.
+    let data = varint_message(1, 9007199254740993);
+    let result = instance.input(1, Direction::Unknown, &[], &data);
+    assert_eq!(result.unwrap().as_deref(), Some("Uid"));
+}
+
+#[test]
+fn metrics_count_from_zero_after_reset() {
+    let instance = instance(&[
+        ("counter.js", r#"
+            var PACKET_NAME = "Counter";
+            function compare(id, header, data) {
+                return false;
+            }
+        "#)
+    ]);
+
+    for id in 1..=3 {
+        instance.input(id, Direction::Unknown, &[], &varint_message(1, 1)).unwrap();
+    }
+    assert_eq!(instance.metrics().packets, 3);
+
+    instance.reset_metrics();
+    let metrics = instance.metrics();
+    assert_eq!(metrics.packets, 0);
+    assert!(metrics.invocations.is_empty());
+
+    // Packets fed after the reset are counted from zero.
+    for id in 1..=2 {
+        instance.input(id, Direction::Unknown, &[], &varint_message(1, 1)).unwrap();
+    }
+
+    let metrics = instance.metrics();
+    assert_eq!(metrics.packets, 2);
+    assert_eq!(metrics.decode_failures, 0);
+    assert_eq!(metrics.invocations.values().sum::<u64>(), 2);
+}
+
+#[test]
+fn identification_is_recorded_again_after_undo() {
+    let instance = instance(&[
+        ("login.js", r#"
+            var PACKET_NAME = "Login";
+            function compare(id, header, data) {
+                identify("Login", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#)
+    ]);
+
+    instance.input(1, Direction::Unknown, &[], &varint_message(1, 7)).unwrap();
+    assert!(instance.cache().field_known("Login", 1, None));
+
+    assert!(instance.undo());
+    assert!(!instance.cache().field_known("Login", 1, None));
+
+    // Repeating the same identification restores the field.
+    instance.input(1, Direction::Unknown, &[], &varint_message(1, 7)).unwrap();
+    assert!(instance.cache().field_known("Login", 1, None));
+}
+
+#[test]
+fn renamed_packet_is_identified_again_under_the_script_name() {
+    let instance = instance(&[
+        ("handshake.js", r#"
+            var PACKET_NAME = "Handshake";
+            function compare(id, header, data) {
+                identify("Handshake", id, { field_name: "nonce", field_type: "uint64", field_id: 3 });
+                return true;
+            }
+        "#)
+    ]);
+
+    instance.input(12, Direction::Outbound, &[], &varint_message(3, 99)).unwrap();
+    assert!(instance.rename_packet("Handshake", "Hello"));
+    assert!(!instance.cache().field_known("Handshake", 3, None));
+
+    // The script still uses the old name, which is recorded again on the next packet.
+    instance.input(12, Direction::Outbound, &[], &varint_message(3, 100)).unwrap();
+    assert!(instance.cache().field_known("Handshake", 3, None));
+}
+
+#[test]
+fn panicking_listener_does_not_poison_the_cache() {
+    let instance = instance(&[
+        ("record.js", r#"
+            var PACKET_NAME = "Record";
+            var seen = 0;
+            function compare(id, header, data) {
+                seen += 1;
+                identify("Record", id, { field_name: "field_" + seen, field_type: "int32", field_id: seen });
+                return true;
+            }
+        "#)
+    ]);
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    instance.on_cache_update(|_, _| panic!("listener failed"));
+    instance.on_cache_update({
+        let calls = calls.clone();
+        move |_, _| {
+            calls.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    instance.input(4, Direction::Inbound, &[], &varint_message(1, 10)).unwrap();
+    instance.input(4, Direction::Inbound, &[], &varint_message(2, 20)).unwrap();
+
+    // Every change reached the second listener, and the cache can still be read.
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    let cache = instance.cache();
+    assert!(cache.field_known("Record", 1, None) && cache.field_known("Record", 2, None));
+}
+
+#[test]
+fn feed_raw_reports_each_matching_script_and_bad_headers() {
+    let instance = instance(&[
+        ("first.js", r#"
+            var PACKET_NAME = "Login";
+            function compare(id, header, data) {
+                identify("Login", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#),
+        ("second.js", r#"
+            var PACKET_NAME = "Logout";
+            var ALWAYS_RUN = true;
+            function compare(id, header, data) {
+                return true;
+            }
+        "#)
+    ]);
+
+    let outcome = instance.feed_raw(1, Direction::Unknown, &[], &varint_message(1, 7)).unwrap();
+    let FeedOutcome::Matched(results) = outcome else {
+        panic!("expected a match, found {:?}", outcome);
+    };
+
+    let mut scripts = results.iter().map(|matched| matched.script.as_str()).collect::<Vec<_>>();
+    scripts.sort();
+    assert_eq!(scripts, ["first.js", "second.js"]);
+    assert!(results.iter().all(|matched| matched.name.as_deref() == Some("Login")));
+
+    // A truncated header is reported even though `strict_header` is not set.
+    let outcome = instance.feed_raw(2, Direction::Unknown, &[0x0A, 0x05], &varint_message(1, 7)).unwrap();
+    assert!(matches!(outcome, FeedOutcome::HeaderDecodeFailed(_)), "unexpected outcome {:?}", outcome);
+}
+
+#[test]
+fn init_writes_to_the_live_cache() {
+    let instance = instance(&[
+        ("login.js", r#"
+            var PACKET_NAME = "Login";
+            function init(env, cache) {
+                cache.guessName("Login", 3);
+                if (!cache.isKnown(3)) throw "cache handle is not live";
+                if (cache.toJSON().id_map["3"] !== "Login") throw "snapshot is missing the guess";
+            }
+            function compare(id, header, data) {
+                return false;
+            }
+        "#)
+    ]);
+
+    assert_eq!(instance.cache().name_of(3), Some("Login"));
+}
+
+#[test]
+fn teardown_runs_only_on_the_loading_thread() {
+    let script = r#"
+        var PACKET_NAME = "Login";
+        function teardown() {
+            guessName("TornDown", 9);
+        }
+        function compare(id, header, data) {
+            return false;
+        }
+    "#;
+
+    // Shutting down on the loading thread tears down the scripts.
+    let loaded = instance(&[("login.js", script)]);
+    loaded.shutdown().unwrap();
+    assert_eq!(loaded.cache().name_of(9), Some("TornDown"));
+
+    // Dropping the instance on another thread leaks the scripts instead of tearing them down there.
+    let moved = instance(&[("login.js", script)]);
+    std::thread::spawn(move || drop(moved)).join().unwrap();
+}
+
+#[test]
+fn dry_run_results_follow_the_real_cache() {
+    let schema = std::env::temp_dir().join(format!("biscuit-dry-run-{}.proto", std::process::id()));
+    std::fs::write(&schema, "message Ping { uint32 time = 1; }").unwrap();
+
+    let config = Config {
+        allow_any_thread: true,
+        worker_threads: 0,
+        dry_run: true,
+        proto_schema: Some(schema.to_string_lossy().to_string()),
+        ..Config::default()
+    };
+    let sources = HashMap::from([("login.js".to_string(), r#"
+        var PACKET_NAME = "Login";
+        function compare(id, header, data) {
+            identify("Login", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+            return true;
+        }
+    "#.to_string())]);
+
+    let instance = Biscuit::new();
+    let result = instance.initialize_from_sources(config, sources);
+    std::fs::remove_file(&schema).unwrap();
+    result.unwrap();
+
+    instance.input(1, Direction::Unknown, &[], &varint_message(1, 7)).unwrap();
+    assert!(!instance.cache().field_known("Login", 1, None));
+    assert!(instance.dry_run_results().field_known("Login", 1, None));
+
+    // Changes to the real cache are reflected in the scratch cache, which keeps the scripts' changes.
+    assert!(instance.rename_packet("Ping", "Pong"));
+    let results = instance.dry_run_results();
+    assert!(results.fields("Pong").is_some());
+    assert!(results.fields("Ping").is_none());
+    assert!(results.field_known("Login", 1, None));
+}
+
+#[test]
+fn dry_run_results_are_empty_when_disabled() {
+    let instance = instance(&[
+        ("login.js", r#"
+            var PACKET_NAME = "Login";
+            function compare(id, header, data) {
+                identify("Login", id, { field_name: "value", field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#)
+    ]);
+
+    instance.input(1, Direction::Unknown, &[], &varint_message(1, 7)).unwrap();
+    assert!(instance.cache().field_known("Login", 1, None));
+    assert!(instance.dry_run_results().known_names().is_empty());
+}
+
+#[test]
+fn warmup_skips_samples_which_fail_to_decode() {
+    let instance = instance(&[
+        ("strict.js", r#"
+            var PACKET_NAME = "Ping";
+            function compare(id, header, data) {
+                if (data.get(1) > 100) {
+                    throw new Error("value out of range");
+                }
+                return false;
+            }
+        "#)
+    ]);
+
+    let samples = vec![
+        (7, vec![], vec![0x0F, 0x00]),
+        (7, vec![], varint_message(1, 5)),
+        (7, vec![], vec![0x0A, 0x09, 0x01]),
+        (7, vec![], varint_message(1, 500))
+    ];
+    let summary = instance.warmup(&samples).unwrap();
+
+    let failed = summary.decode_errors.iter().map(|error| error.index).collect::<Vec<_>>();
+    assert_eq!(failed, [0, 2]);
+    assert!(summary.decode_errors.iter().all(|error| error.packet_id == 7));
+
+    // The samples after the first malformed one were still run.
+    let [report] = summary.reports.as_slice() else {
+        panic!("expected one report, found {:?}", summary.reports);
+    };
+    assert_eq!(report.runs, 2);
+    assert_eq!(report.errors.len(), 1);
+    assert!(!summary.succeeded());
+}
+
+#[test]
+fn inferred_types_follow_the_encoding() {
+    let instance = instance(&[
+        ("position.js", r#"
+            var PACKET_NAME = "Position";
+            function compare(id, header, data) {
+                for (var field of [1, 2, 3, 4]) {
+                    identify("Position", id, { field_name: "f" + field, field_type: inferType(data, field), field_id: field });
+                }
+                return inferType(data, 9) === undefined;
+            }
+        "#)
+    ]);
+
+    // A double holding 3.0, a float, a varint which occurs twice, and a nested message.
+    let mut data = vec![0x09];
+    data.extend(3.0f64.to_le_bytes());
+    data.push(0x15);
+    data.extend(1.5f32.to_le_bytes());
+    data.extend([0x18, 0x01, 0x18, 0x02]);
+    data.extend([0x22, 0x03, 0x08, 0x96, 0x01]);
+
+    let name = instance.input(30, Direction::Inbound, &[], &data).unwrap();
+    assert_eq!(name.as_deref(), Some("Position"));
+
+    let cache = instance.cache();
+    let types = cache.fields("Position").unwrap()
+        .iter()
+        .map(|field| (field.field_id, field.field_type.as_str()))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(types[&1], "double");
+    assert_eq!(types[&2], "float");
+    assert_eq!(types[&3], "repeated int64");
+    assert_eq!(types[&4], "bytes");
+}
+
+#[test]
+fn nested_messages_have_methods_only_when_enabled() {
+    let plain = r#"
+        var PACKET_NAME = "Nested";
+        function compare(id, header, data) {
+            var nested = data.get(1);
+            if (nested.inner.get(1) === 150 && nested.get === undefined) {
+                identify("Nested", id, { field_name: "inner", field_type: "bytes", field_id: 1 });
+            }
+            return true;
+        }
+    "#;
+    let methods = r#"
+        var PACKET_NAME = "Nested";
+        function compare(id, header, data) {
+            if (data.get(1).get(1) === 150) {
+                identify("Nested", id, { field_name: "inner", field_type: "bytes", field_id: 1 });
+            }
+            return true;
+        }
+    "#;
+    // Field 1 holds a message whose field 1 is 150, which is not valid UTF-8.
+    let data = [0x0A, 0x03, 0x08, 0x96, 0x01];
+
+    for (nested_message_methods, source) in [(false, plain), (true, methods)] {
+        let config = Config {
+            allow_any_thread: true,
+            nested_message_methods,
+            ..Config::default()
+        };
+        let sources = HashMap::from([("nested.js".to_string(), source.to_string())]);
+
+        let instance = Biscuit::new();
+        instance.initialize_from_sources(config, sources).unwrap();
+
+        let name = instance.input(2, Direction::Inbound, &[], &data).unwrap();
+        assert_eq!(name.as_deref(), Some("Nested"), "nested_message_methods = {}", nested_message_methods);
+    }
+}
+
+#[test]
+fn script_may_declare_its_own_config() {
+    let instance = instance(&[
+        ("settings.js", r#"
+            let config = { name: "Settings" };
+            var PACKET_NAME = config.name;
+            function compare(id, header, data) {
+                identify(config.name, id, { field_name: "flag", field_type: "bool", field_id: 2 });
+                return true;
+            }
+        "#)
+    ]);
+
+    let name = instance.input(40, Direction::Inbound, &[], &varint_message(2, 1)).unwrap();
+    assert_eq!(name.as_deref(), Some("Settings"));
+}
+
+#[test]
+fn script_may_declare_its_own_store() {
+    let instance = instance(&[
+        ("counter.js", r#"
+            const store = [];
+            var PACKET_NAME = "Counter";
+            function compare(id, header, data) {
+                store.push(data.get(1));
+                if (store.length === 2) {
+                    identify("Counter", id, { field_name: "count", field_type: "uint32", field_id: 1 });
+                }
+                return true;
+            }
+        "#)
+    ]);
+
+    assert_eq!(instance.input(41, Direction::Inbound, &[], &varint_message(1, 1)).unwrap(), None);
+    let name = instance.input(41, Direction::Inbound, &[], &varint_message(1, 2)).unwrap();
+    assert_eq!(name.as_deref(), Some("Counter"));
+}
+
+#[test]
+fn script_may_declare_its_own_shared() {
+    let instance = instance(&[
+        ("session.js", r#"
+            let shared = new Set();
+            var PACKET_NAME = "Session";
+            function compare(id, header, data) {
+                shared.add(id);
+                if (shared.has(42)) {
+                    identify("Session", id, { field_name: "token", field_type: "string", field_id: 1 });
+                }
+                return true;
+            }
+        "#)
+    ]);
+
+    let mut data = vec![0x0A, 0x03];
+    data.extend(b"abc");
+    let name = instance.input(42, Direction::Outbound, &[], &data).unwrap();
+    assert_eq!(name.as_deref(), Some("Session"));
+}
+
+#[test]
+fn recursion_is_limited_by_the_config() {
+    let source = r#"
+        var PACKET_NAME = "Tree";
+        function depth(n) {
+            return n === 0 ? 0 : 1 + depth(n - 1);
+        }
+        function compare(id, header, data) {
+            try {
+                depth(data.get(1));
+            } catch (error) {
+                return false;
+            }
+            identify("Tree", id, { field_name: "depth", field_type: "uint32", field_id: 1 });
+            return true;
+        }
+    "#;
+    let config = Config {
+        allow_any_thread: true,
+        max_recursion_depth: Some(32),
+        ..Config::default()
+    };
+
+    let instance = Biscuit::new();
+    instance.initialize_from_sources(config, HashMap::from([("tree.js".to_string(), source.to_string())])).unwrap();
+
+    assert_eq!(instance.input(8, Direction::Inbound, &[], &varint_message(1, 100)).unwrap(), None);
+    assert_eq!(instance.input(8, Direction::Inbound, &[], &varint_message(1, 10)).unwrap().as_deref(), Some("Tree"));
+}
+
+#[test]
+fn require_works_while_comparing() {
+    let root = files_on_disk("require-compare", &[
+        ("codes.js", r#"
+            var PACKET_NAME = "Status";
+            function compare(id, header, data) {
+                var codes = require("./lib/codes.js");
+                identify("Status", id, { field_name: codes.name(data.get(1)), field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#),
+        ("lib/codes.js", r#"
+            module.exports.name = function (code) {
+                return code === 200 ? "ok" : "error";
+            };
+        "#)
+    ]);
+
+    let result = instance_from_directory(&root)
+        .and_then(|instance| instance.input(3, Direction::Inbound, &[], &varint_message(1, 200)).map(|_| instance));
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let cache = result.unwrap().cache();
+    assert_eq!(cache.field_id("Status", "ok"), Some(1));
+}
+
+#[test]
+fn require_rejects_paths_outside_the_script_directory() {
+    let root = files_on_disk("require-root", &[
+        ("outside.js", r#"module.exports = { name: "outside" };"#),
+        ("scripts/lib/names.js", r#"module.exports = { name: "inside" };"#),
+        ("scripts/guarded.js", r#"
+            var PACKET_NAME = "Guarded";
+            function attempt(path) {
+                try {
+                    return require(path).name;
+                } catch (error) {
+                    return "rejected";
+                }
+            }
+            var paths = ["./lib/names.js", "../outside.js", "lib/../../outside.js", "/etc/passwd"];
+            var names = paths.map(attempt);
+            function compare(id, header, data) {
+                names.forEach(function (name, index) {
+                    identify("Guarded", id, { field_name: name, field_type: "bytes", field_id: index + 1 });
+                });
+                return true;
+            }
+        "#)
+    ]);
+
+    let result = instance_from_directory(&root.join("scripts"))
+        .and_then(|instance| instance.input(6, Direction::Inbound, &[], &varint_message(1, 1)).map(|_| instance));
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let cache = result.unwrap().cache();
+    let names = cache.fields("Guarded").unwrap()
+        .iter()
+        .map(|field| field.field_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["inside", "rejected", "rejected", "rejected"]);
+}
+
+#[test]
+fn first_decrypting_script_by_name_decrypts_every_packet() {
+    // Each decrypter overwrites the value of field 1, so the result shows which one ran.
+    let decrypter = |value: u8| format!(r#"
+        var PACKET_NAME = "Session";
+        var DECRYPTS = true;
+        function decrypt(id, data) {{
+            var bytes = new Uint8Array(data.slice(0));
+            bytes[1] = {};
+            return bytes.buffer;
+        }}
+        function compare(id, header, data) {{
+            return false;
+        }}
+    "#, value);
+    let instance = instance(&[
+        ("b_session.js", &decrypter(22)),
+        ("a_session.js", &decrypter(11)),
+        // Without `DECRYPTS`, this function is never run.
+        ("0_unrelated.js", r#"
+            var PACKET_NAME = "Unrelated";
+            function decrypt(id, data) {
+                return new ArrayBuffer(0);
+            }
+            function compare(id, header, data) {
+                return false;
+            }
+        "#),
+        ("reader.js", r#"
+            var PACKET_NAME = "Sealed";
+            function compare(id, header, data) {
+                identify("Sealed", id, { field_name: "value_" + data.get(1), field_type: "uint32", field_id: 1 });
+                return true;
+            }
+        "#)
+    ]);
+
+    instance.input(50, Direction::Inbound, &[], &varint_message(1, 99)).unwrap();
+    assert_eq!(instance.cache().field_id("Sealed", "value_11"), Some(1));
+}
+
+#[test]
+fn strict_init_lists_every_failed_script() {
+    let sources = HashMap::from([
+        ("broken_syntax.js".to_string(), "function compare(id, header, data) {".to_string()),
+        ("missing_name.js".to_string(), "function compare(id, header, data) { return false; }".to_string()),
+        ("valid.js".to_string(), r#"
+            var PACKET_NAME = "Valid";
+            function compare(id, header, data) {
+                return false;
+            }
+        "#.to_string())
+    ]);
+
+    for worker_threads in [0, 2] {
+        let config = Config {
+            allow_any_thread: true,
+            strict_init: true,
+            worker_threads,
+            ..Config::default()
+        };
+
+        let error = Biscuit::new()
+            .initialize_from_sources(config, sources.clone())
+            .expect_err("strict_init should reject the broken scripts");
+        let message = format!("{:#}", anyhow::Error::from(error));
+        assert!(message.contains("broken_syntax.js"), "{}", message);
+        assert!(message.contains("missing_name.js"), "{}", message);
+        assert!(!message.contains("valid.js"), "{}", message);
+    }
+
+    // Without `strict_init`, the valid script is still loaded.
+    let config = Config { allow_any_thread: true, ..Config::default() };
+    Biscuit::new().initialize_from_sources(config, sources).unwrap();
+}